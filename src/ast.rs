@@ -5,6 +5,13 @@ use serde::{Deserialize, Serialize};
 pub struct Program {
     pub statements: Vec<Node>,
     pub errors: Vec<ParseError>,
+    pub warnings: Vec<WarningFrame>,
+}
+
+impl Default for Program {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Program {
@@ -12,6 +19,7 @@ impl Program {
         Self {
             statements: Vec::new(),
             errors: Vec::new(),
+            warnings: Vec::new(),
         }
     }
 }
@@ -19,44 +27,696 @@ impl Program {
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub enum Node {
     Variable(VariableNode),
+    Use(UseNode),
+    UsePath(UsePathNode),
+    UseGlob(UseGlobNode),
+    UseGroup(UseGroupNode),
+    Function(FunctionNode),
+    TypeAlias(TypeAliasNode),
+    Record(RecordNode),
+    Field(FieldNode),
+    Trait(TraitNode),
+    Impl(ImplNode),
+    AssociatedType(AssociatedTypeNode),
+    WherePredicate(WherePredicateNode),
+    SelfParam(SelfParamNode),
+    Parameter(ParameterNode),
+    GenericParam(GenericParamNode),
+    Block(BlockNode),
+    AsyncBlock(AsyncBlockNode),
+    Expression(ExpressionNode),
+    NamedType(NamedTypeNode),
+    ReferenceType(ReferenceTypeNode),
+    ArrayType(ArrayTypeNode),
+    TupleType(TupleTypeNode),
+    FunctionType(FunctionTypeNode),
+    UnionType(UnionTypeNode),
+    VoidType(Span),
     Return(ReturnNode),
     If(IfNode),
+    While(WhileNode),
+    For(ForNode),
+    Break(BreakNode),
+    Continue(ContinueNode),
     Keyword(KeywordNode),
     Ident(IdentNode),
+    Path(PathNode),
     Number(NumberNode),
+    String(StringNode),
+    ByteString(ByteStringNode),
+    Char(CharNode),
+    Bool(BoolNode),
+    Template(TemplateNode),
+    Array(ArrayNode),
+    ArrayRepeat(ArrayRepeatNode),
+    Tuple(TupleNode),
+    Struct(StructNode),
+    FieldInit(FieldInitNode),
+    Binary(BinaryNode),
+    Unary(UnaryNode),
+    Assign(AssignNode),
+    Group(GroupNode),
+    Call(CallNode),
+    Index(IndexNode),
+    Member(MemberNode),
+    Await(AwaitNode),
+    Closure(ClosureNode),
+    Conditional(ConditionalNode),
+    Range(RangeNode),
+    SelfValue(Span),
+    Match(MatchNode),
+    MatchArm(MatchArmNode),
+    WildcardPattern(Span),
+    RestPattern(Span),
+    TuplePattern(TuplePatternNode),
+    StructPattern(StructPatternNode),
+    FieldPattern(FieldPatternNode),
+    VariantPattern(VariantPatternNode),
+    RangePattern(RangePatternNode),
+    OrPattern(OrPatternNode),
     Semi(Span),
 }
 
+impl Node {
+    pub fn span(&self) -> Span {
+        match self {
+            Node::Variable(VariableNode { span, .. })
+            | Node::Use(UseNode { span, .. })
+            | Node::UsePath(UsePathNode { span, .. })
+            | Node::UseGlob(UseGlobNode { span, .. })
+            | Node::UseGroup(UseGroupNode { span, .. })
+            | Node::Function(FunctionNode { span, .. })
+            | Node::TypeAlias(TypeAliasNode { span, .. })
+            | Node::Record(RecordNode { span, .. })
+            | Node::Field(FieldNode { span, .. })
+            | Node::Trait(TraitNode { span, .. })
+            | Node::Impl(ImplNode { span, .. })
+            | Node::AssociatedType(AssociatedTypeNode { span, .. })
+            | Node::WherePredicate(WherePredicateNode { span, .. })
+            | Node::SelfParam(SelfParamNode { span, .. })
+            | Node::Parameter(ParameterNode { span, .. })
+            | Node::GenericParam(GenericParamNode { span, .. })
+            | Node::Block(BlockNode { span, .. })
+            | Node::AsyncBlock(AsyncBlockNode { span, .. })
+            | Node::Expression(ExpressionNode { span, .. })
+            | Node::NamedType(NamedTypeNode { span, .. })
+            | Node::ReferenceType(ReferenceTypeNode { span, .. })
+            | Node::ArrayType(ArrayTypeNode { span, .. })
+            | Node::TupleType(TupleTypeNode { span, .. })
+            | Node::FunctionType(FunctionTypeNode { span, .. })
+            | Node::UnionType(UnionTypeNode { span, .. })
+            | Node::VoidType(span)
+            | Node::Return(ReturnNode { span, .. })
+            | Node::If(IfNode { span, .. })
+            | Node::While(WhileNode { span, .. })
+            | Node::For(ForNode { span, .. })
+            | Node::Break(BreakNode { span, .. })
+            | Node::Continue(ContinueNode { span, .. })
+            | Node::Keyword(KeywordNode { span, .. })
+            | Node::Ident(IdentNode { span, .. })
+            | Node::Path(PathNode { span, .. })
+            | Node::Number(NumberNode { span, .. })
+            | Node::String(StringNode { span, .. })
+            | Node::ByteString(ByteStringNode { span, .. })
+            | Node::Char(CharNode { span, .. })
+            | Node::Bool(BoolNode { span, .. })
+            | Node::Template(TemplateNode { span, .. })
+            | Node::Array(ArrayNode { span, .. })
+            | Node::ArrayRepeat(ArrayRepeatNode { span, .. })
+            | Node::Tuple(TupleNode { span, .. })
+            | Node::Struct(StructNode { span, .. })
+            | Node::FieldInit(FieldInitNode { span, .. })
+            | Node::Binary(BinaryNode { span, .. })
+            | Node::Unary(UnaryNode { span, .. })
+            | Node::Assign(AssignNode { span, .. })
+            | Node::Group(GroupNode { span, .. })
+            | Node::Call(CallNode { span, .. })
+            | Node::Index(IndexNode { span, .. })
+            | Node::Member(MemberNode { span, .. })
+            | Node::Await(AwaitNode { span, .. })
+            | Node::Closure(ClosureNode { span, .. })
+            | Node::Conditional(ConditionalNode { span, .. })
+            | Node::Range(RangeNode { span, .. })
+            | Node::SelfValue(span)
+            | Node::Match(MatchNode { span, .. })
+            | Node::MatchArm(MatchArmNode { span, .. })
+            | Node::WildcardPattern(span)
+            | Node::RestPattern(span)
+            | Node::TuplePattern(TuplePatternNode { span, .. })
+            | Node::StructPattern(StructPatternNode { span, .. })
+            | Node::FieldPattern(FieldPatternNode { span, .. })
+            | Node::VariantPattern(VariantPatternNode { span, .. })
+            | Node::RangePattern(RangePatternNode { span, .. })
+            | Node::OrPattern(OrPatternNode { span, .. })
+            | Node::Semi(span) => *span,
+        }
+    }
+
+    /// The name of the variant, such as `"Variable"`, as it appears in the
+    /// serialized tree.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Node::Variable(_) => "Variable",
+            Node::Use(_) => "Use",
+            Node::UsePath(_) => "UsePath",
+            Node::UseGlob(_) => "UseGlob",
+            Node::UseGroup(_) => "UseGroup",
+            Node::Function(_) => "Function",
+            Node::TypeAlias(_) => "TypeAlias",
+            Node::Record(_) => "Record",
+            Node::Field(_) => "Field",
+            Node::Trait(_) => "Trait",
+            Node::Impl(_) => "Impl",
+            Node::AssociatedType(_) => "AssociatedType",
+            Node::WherePredicate(_) => "WherePredicate",
+            Node::SelfParam(_) => "SelfParam",
+            Node::Parameter(_) => "Parameter",
+            Node::GenericParam(_) => "GenericParam",
+            Node::Block(_) => "Block",
+            Node::AsyncBlock(_) => "AsyncBlock",
+            Node::Expression(_) => "Expression",
+            Node::NamedType(_) => "NamedType",
+            Node::ReferenceType(_) => "ReferenceType",
+            Node::ArrayType(_) => "ArrayType",
+            Node::TupleType(_) => "TupleType",
+            Node::FunctionType(_) => "FunctionType",
+            Node::UnionType(_) => "UnionType",
+            Node::VoidType(_) => "VoidType",
+            Node::Return(_) => "Return",
+            Node::If(_) => "If",
+            Node::While(_) => "While",
+            Node::For(_) => "For",
+            Node::Break(_) => "Break",
+            Node::Continue(_) => "Continue",
+            Node::Keyword(_) => "Keyword",
+            Node::Ident(_) => "Ident",
+            Node::Path(_) => "Path",
+            Node::Number(_) => "Number",
+            Node::String(_) => "String",
+            Node::ByteString(_) => "ByteString",
+            Node::Char(_) => "Char",
+            Node::Bool(_) => "Bool",
+            Node::Template(_) => "Template",
+            Node::Array(_) => "Array",
+            Node::ArrayRepeat(_) => "ArrayRepeat",
+            Node::Tuple(_) => "Tuple",
+            Node::Struct(_) => "Struct",
+            Node::FieldInit(_) => "FieldInit",
+            Node::Binary(_) => "Binary",
+            Node::Unary(_) => "Unary",
+            Node::Assign(_) => "Assign",
+            Node::Group(_) => "Group",
+            Node::Call(_) => "Call",
+            Node::Index(_) => "Index",
+            Node::Member(_) => "Member",
+            Node::Await(_) => "Await",
+            Node::Closure(_) => "Closure",
+            Node::Conditional(_) => "Conditional",
+            Node::Range(_) => "Range",
+            Node::SelfValue(_) => "SelfValue",
+            Node::Match(_) => "Match",
+            Node::MatchArm(_) => "MatchArm",
+            Node::WildcardPattern(_) => "WildcardPattern",
+            Node::RestPattern(_) => "RestPattern",
+            Node::TuplePattern(_) => "TuplePattern",
+            Node::StructPattern(_) => "StructPattern",
+            Node::FieldPattern(_) => "FieldPattern",
+            Node::VariantPattern(_) => "VariantPattern",
+            Node::RangePattern(_) => "RangePattern",
+            Node::OrPattern(_) => "OrPattern",
+            Node::Semi(_) => "Semi",
+        }
+    }
+
+    /// The nodes directly below this one, in source order.
+    pub fn children(&self) -> Vec<&Node> {
+        fn boxed(node: &Option<Box<Node>>) -> Vec<&Node> {
+            node.iter().map(|node| node.as_ref()).collect()
+        }
+
+        match self {
+            Node::Variable(node) => [
+                vec![node.keyword.as_ref(), &node.identifier],
+                boxed(&node.type_annotation),
+                vec![&node.initializer, &node.semi],
+            ]
+            .concat(),
+            Node::Use(node) => vec![&node.tree, &node.semi],
+            Node::UsePath(node) => [node.path.iter().collect(), boxed(&node.alias)].concat(),
+            Node::UseGlob(node) => node.path.iter().collect(),
+            Node::UseGroup(node) => node.path.iter().chain(&node.items).collect(),
+            Node::Function(node) => [
+                vec![node.identifier.as_ref()],
+                node.generics.iter().chain(&node.parameters).collect(),
+                boxed(&node.return_type),
+                boxed(&node.body),
+            ]
+            .concat(),
+            Node::TypeAlias(node) => [
+                vec![node.identifier.as_ref()],
+                node.generics.iter().collect(),
+                vec![&node.aliased, &node.semi],
+            ]
+            .concat(),
+            Node::Record(node) => [
+                vec![node.identifier.as_ref()],
+                node.generics.iter().chain(&node.fields).collect(),
+                vec![&node.semi],
+            ]
+            .concat(),
+            Node::Field(node) => vec![&node.identifier, &node.type_annotation],
+            Node::Trait(node) => [
+                vec![node.identifier.as_ref()],
+                node.generics
+                    .iter()
+                    .chain(&node.where_clause)
+                    .chain(&node.items)
+                    .collect(),
+            ]
+            .concat(),
+            Node::Impl(node) => [
+                node.generics.iter().collect(),
+                boxed(&node.trait_type),
+                vec![&node.self_type],
+                node.where_clause.iter().chain(&node.items).collect(),
+            ]
+            .concat(),
+            Node::AssociatedType(node) => [
+                vec![node.identifier.as_ref()],
+                node.bounds.iter().collect(),
+                vec![&node.semi],
+            ]
+            .concat(),
+            Node::WherePredicate(node) => std::iter::once(node.bounded.as_ref())
+                .chain(&node.bounds)
+                .collect(),
+            Node::Parameter(node) => {
+                [vec![node.identifier.as_ref()], boxed(&node.type_annotation)].concat()
+            }
+            Node::GenericParam(node) => std::iter::once(node.identifier.as_ref())
+                .chain(&node.bounds)
+                .collect(),
+            Node::Block(node) => node.statements.iter().collect(),
+            Node::AsyncBlock(node) => vec![&node.body],
+            Node::Expression(node) => [vec![node.expression.as_ref()], boxed(&node.semi)].concat(),
+            Node::NamedType(node) => std::iter::once(node.identifier.as_ref())
+                .chain(&node.arguments)
+                .collect(),
+            Node::ReferenceType(node) => vec![&node.referent],
+            Node::ArrayType(node) => [vec![node.element.as_ref()], boxed(&node.length)].concat(),
+            Node::TupleType(node) => node.elements.iter().collect(),
+            Node::FunctionType(node) => node
+                .parameters
+                .iter()
+                .chain(std::iter::once(node.return_type.as_ref()))
+                .collect(),
+            Node::UnionType(node) => node.types.iter().collect(),
+            Node::Return(node) => [boxed(&node.value), vec![&node.semi]].concat(),
+            Node::If(node) => [
+                vec![node.condition.as_ref(), &node.then_block],
+                boxed(&node.else_branch),
+            ]
+            .concat(),
+            Node::While(node) => [boxed(&node.label), vec![&node.condition, &node.body]].concat(),
+            Node::For(node) => [
+                boxed(&node.label),
+                vec![&node.binding, &node.iterable, &node.body],
+            ]
+            .concat(),
+            Node::Break(node) => {
+                [boxed(&node.label), boxed(&node.value), vec![&node.semi]].concat()
+            }
+            Node::Continue(node) => [boxed(&node.label), vec![&node.semi]].concat(),
+            Node::Path(node) => node.segments.iter().collect(),
+            Node::Template(node) => node.expressions.iter().collect(),
+            Node::Array(node) => node.elements.iter().collect(),
+            Node::ArrayRepeat(node) => vec![&node.value, &node.length],
+            Node::Tuple(node) => node.elements.iter().collect(),
+            Node::Struct(node) => [
+                vec![node.path.as_ref()],
+                node.fields.iter().collect(),
+                boxed(&node.base),
+            ]
+            .concat(),
+            Node::FieldInit(node) => [vec![node.identifier.as_ref()], boxed(&node.value)].concat(),
+            Node::Binary(node) => vec![&node.left, &node.right],
+            Node::Unary(node) => vec![&node.operand],
+            Node::Assign(node) => vec![&node.target, &node.value],
+            Node::Group(node) => vec![&node.expression],
+            Node::Call(node) => std::iter::once(node.callee.as_ref())
+                .chain(&node.arguments)
+                .collect(),
+            Node::Index(node) => vec![&node.target, &node.index],
+            Node::Member(node) => vec![&node.object, &node.property],
+            Node::Await(node) => vec![&node.expression],
+            Node::Closure(node) => [
+                node.parameters.iter().collect(),
+                boxed(&node.return_type),
+                vec![&node.body],
+            ]
+            .concat(),
+            Node::Conditional(node) => vec![&node.condition, &node.consequent, &node.alternative],
+            Node::Range(node) => vec![&node.start, &node.end],
+            Node::Match(node) => std::iter::once(node.subject.as_ref())
+                .chain(&node.arms)
+                .collect(),
+            Node::MatchArm(node) => [
+                vec![node.pattern.as_ref()],
+                boxed(&node.guard),
+                vec![&node.body],
+            ]
+            .concat(),
+            Node::TuplePattern(node) => node.elements.iter().collect(),
+            Node::StructPattern(node) => node.path.iter().chain(&node.fields).collect(),
+            Node::FieldPattern(node) => {
+                [vec![node.identifier.as_ref()], boxed(&node.pattern)].concat()
+            }
+            Node::VariantPattern(node) => node
+                .path
+                .iter()
+                .chain(node.arguments.iter().flatten())
+                .collect(),
+            Node::RangePattern(node) => vec![&node.start, &node.end],
+            Node::OrPattern(node) => node.alternatives.iter().collect(),
+            Node::SelfParam(_)
+            | Node::VoidType(_)
+            | Node::Keyword(_)
+            | Node::Ident(_)
+            | Node::Number(_)
+            | Node::String(_)
+            | Node::ByteString(_)
+            | Node::Char(_)
+            | Node::Bool(_)
+            | Node::SelfValue(_)
+            | Node::WildcardPattern(_)
+            | Node::RestPattern(_)
+            | Node::Semi(_) => Vec::new(),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub enum ParseError {
     UnexpectedToken(TokenFrame),
     UnexpectedEndOfInput,
     UnexpectedKeyword(Keyword),
+    BreakOutsideLoop(Span),
+    ContinueOutsideLoop(Span),
+    UndeclaredLabel(IdentNode),
+    AwaitOutsideAsync(Span),
+    InvalidAssignmentTarget(Span),
+    /// A number literal out of range for its suffix type.
+    NumberOverflow(Span),
+    TooManyErrors,
 }
 
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Clone, Copy, Deserialize, Serialize)]
 pub struct Span {
     pub start: usize,
     pub end: usize,
+    pub line: usize,
+    pub column: usize,
 }
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct VariableNode {
     pub span: Span,
+    pub docs: Option<String>,
+    pub visibility: Visibility,
     pub keyword: Box<Node>,
     pub identifier: Box<Node>,
-    pub literal: Box<Node>,
+    pub type_annotation: Option<Box<Node>>,
+    pub initializer: Box<Node>,
+    pub semi: Box<Node>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub enum Visibility {
+    Private,
+    Public,
+}
+
+/// `use path::to::item;`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct UseNode {
+    pub span: Span,
+    pub visibility: Visibility,
+    pub tree: Box<Node>,
     pub semi: Box<Node>,
 }
 
+/// A single import, optionally renamed: `path::to::item as other`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct UsePathNode {
+    pub span: Span,
+    pub path: Vec<Node>,
+    pub alias: Option<Box<Node>>,
+}
+
+/// Everything public in a module: `path::to::*`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct UseGlobNode {
+    pub span: Span,
+    pub path: Vec<Node>,
+}
+
+/// Several imports sharing a prefix: `path::{a, b as c, d::*}`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct UseGroupNode {
+    pub span: Span,
+    pub path: Vec<Node>,
+    pub items: Vec<Node>,
+}
+
+/// `pub async func name<T>(a: A, b: B): R { ... }`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct FunctionNode {
+    pub span: Span,
+    pub docs: Option<String>,
+    pub visibility: Visibility,
+    pub is_async: bool,
+    pub identifier: Box<Node>,
+    pub generics: Vec<Node>,
+    pub parameters: Vec<Node>,
+    pub return_type: Option<Box<Node>>,
+    /// Missing for method signatures in a trait.
+    pub body: Option<Box<Node>>,
+}
+
+/// `type Id = u64;`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct TypeAliasNode {
+    pub span: Span,
+    pub docs: Option<String>,
+    pub visibility: Visibility,
+    pub identifier: Box<Node>,
+    pub generics: Vec<Node>,
+    pub aliased: Box<Node>,
+    pub semi: Box<Node>,
+}
+
+/// `type Point = { x: f64, y: f64 };`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct RecordNode {
+    pub span: Span,
+    pub docs: Option<String>,
+    pub visibility: Visibility,
+    pub identifier: Box<Node>,
+    pub generics: Vec<Node>,
+    pub fields: Vec<Node>,
+    pub semi: Box<Node>,
+}
+
+/// A field in a record declaration: `x: f64`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct FieldNode {
+    pub span: Span,
+    pub identifier: Box<Node>,
+    pub type_annotation: Box<Node>,
+}
+
+/// `trait Name<T> where T: Bound { ... }`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct TraitNode {
+    pub span: Span,
+    pub docs: Option<String>,
+    pub visibility: Visibility,
+    pub identifier: Box<Node>,
+    pub generics: Vec<Node>,
+    pub where_clause: Vec<Node>,
+    pub items: Vec<Node>,
+}
+
+/// `impl<T> Trait for Type where T: Bound { ... }`, or an inherent
+/// `impl Type { ... }` without a trait.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ImplNode {
+    pub span: Span,
+    pub generics: Vec<Node>,
+    pub trait_type: Option<Box<Node>>,
+    pub self_type: Box<Node>,
+    pub where_clause: Vec<Node>,
+    pub items: Vec<Node>,
+}
+
+/// An associated type declared in a trait: `type Item: Bound;`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct AssociatedTypeNode {
+    pub span: Span,
+    pub docs: Option<String>,
+    pub identifier: Box<Node>,
+    pub bounds: Vec<Node>,
+    pub semi: Box<Node>,
+}
+
+/// A single `Type: Bound + Other` constraint in a where clause.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct WherePredicateNode {
+    pub span: Span,
+    pub bounded: Box<Node>,
+    pub bounds: Vec<Node>,
+}
+
+/// The `self` or `&self` receiver of a method.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct SelfParamNode {
+    pub span: Span,
+    pub is_reference: bool,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ParameterNode {
+    pub span: Span,
+    pub identifier: Box<Node>,
+    /// Only closure parameters may leave out their type.
+    pub type_annotation: Option<Box<Node>>,
+}
+
+/// A generic parameter such as `T` or `T: Bound + Other`.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct GenericParamNode {
+    pub span: Span,
+    pub identifier: Box<Node>,
+    pub bounds: Vec<Node>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct BlockNode {
+    pub span: Span,
+    pub statements: Vec<Node>,
+}
+
+/// `async { ... }`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct AsyncBlockNode {
+    pub span: Span,
+    pub body: Box<Node>,
+}
+
+/// An expression used as a statement, such as a call: `log(x);`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ExpressionNode {
+    pub span: Span,
+    pub expression: Box<Node>,
+    /// Missing for the value a block ends with: `{ a; b }`
+    pub semi: Option<Box<Node>>,
+}
+
+/// A type referred to by name, with any generic arguments: `Vec<T>`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct NamedTypeNode {
+    pub span: Span,
+    pub identifier: Box<Node>,
+    pub arguments: Vec<Node>,
+}
+
+/// `&T`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ReferenceTypeNode {
+    pub span: Span,
+    pub referent: Box<Node>,
+}
+
+/// `[T; N]`, or `[T]` when the length is not part of the type.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ArrayTypeNode {
+    pub span: Span,
+    pub element: Box<Node>,
+    pub length: Option<Box<Node>>,
+}
+
+/// `(A, B)`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct TupleTypeNode {
+    pub span: Span,
+    pub elements: Vec<Node>,
+}
+
+/// `(A, B) => R`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct FunctionTypeNode {
+    pub span: Span,
+    pub parameters: Vec<Node>,
+    pub return_type: Box<Node>,
+}
+
+/// `A | B | C`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct UnionTypeNode {
+    pub span: Span,
+    pub types: Vec<Node>,
+}
+
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct ReturnNode {
     pub span: Span,
+    pub value: Option<Box<Node>>,
+    pub semi: Box<Node>,
 }
 
+/// `if condition { ... } else ...` where the else branch is either a block
+/// or another `IfNode` for `else if`.
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct IfNode {
     pub span: Span,
+    pub condition: Box<Node>,
+    pub then_block: Box<Node>,
+    pub else_branch: Option<Box<Node>>,
+}
+
+/// `label: while condition { ... }`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct WhileNode {
+    pub span: Span,
+    pub label: Option<Box<Node>>,
+    pub condition: Box<Node>,
+    pub body: Box<Node>,
+}
+
+/// `label: for binding in iterable { ... }`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ForNode {
+    pub span: Span,
+    pub label: Option<Box<Node>>,
+    pub binding: Box<Node>,
+    pub iterable: Box<Node>,
+    pub body: Box<Node>,
+}
+
+/// `break label value;` where both the label and the value are optional.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct BreakNode {
+    pub span: Span,
+    pub label: Option<Box<Node>>,
+    pub value: Option<Box<Node>>,
+    pub semi: Box<Node>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ContinueNode {
+    pub span: Span,
+    pub label: Option<Box<Node>>,
+    pub semi: Box<Node>,
 }
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
@@ -65,16 +725,306 @@ pub struct KeywordNode {
     pub keyword: Keyword,
 }
 
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
 pub struct IdentNode {
     pub span: Span,
     pub identifier: String,
 }
 
+/// A name qualified by its module: `math::consts::PI`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct PathNode {
+    pub span: Span,
+    pub segments: Vec<Node>,
+}
+
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct NumberNode {
     pub span: Span,
     pub kind: NumberKind,
     pub value: String,
-    pub postfix: Option<String>,
+    pub postfix: Option<NumberSuffix>,
+    /// None if the literal is too large for any type.
+    pub parsed: Option<NumberValue>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct StringNode {
+    pub span: Span,
+    pub value: String,
+}
+
+/// `b"text"`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ByteStringNode {
+    pub span: Span,
+    pub value: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct CharNode {
+    pub span: Span,
+    pub value: char,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct BoolNode {
+    pub span: Span,
+    pub value: bool,
+}
+
+/// A template literal. `strings` holds the raw text around each
+/// interpolation, so it always has one more entry than `expressions`.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct TemplateNode {
+    pub span: Span,
+    pub strings: Vec<String>,
+    pub expressions: Vec<Node>,
+}
+
+/// `[a, b, c]`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ArrayNode {
+    pub span: Span,
+    pub elements: Vec<Node>,
+}
+
+/// `[value; length]`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ArrayRepeatNode {
+    pub span: Span,
+    pub value: Box<Node>,
+    pub length: Box<Node>,
+}
+
+/// `(a, b)`, `(a,)` or the empty tuple `()`.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct TupleNode {
+    pub span: Span,
+    pub elements: Vec<Node>,
+}
+
+/// `Point { x: 1, y, ..base }`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct StructNode {
+    pub span: Span,
+    pub path: Box<Node>,
+    pub fields: Vec<Node>,
+    pub base: Option<Box<Node>>,
+}
+
+/// A field in a struct literal. Without a value, as in `Point { x }`, the
+/// field takes the value of the variable of the same name.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct FieldInitNode {
+    pub span: Span,
+    pub identifier: Box<Node>,
+    pub value: Option<Box<Node>>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
+    EqualTo,
+    NotEqualTo,
+    LessThan,
+    LessThanEqual,
+    GreaterThan,
+    GreaterThanEqual,
+    LogicalAnd,
+    LogicalOr,
+    /// `a ?? b`: `b` when `a` is null.
+    Coalesce,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub enum UnaryOperator {
+    Not,
+    Negate,
+}
+
+/// `=` or one of the compound forms such as `+=`, named after the
+/// operation they apply.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub enum AssignOperator {
+    Assign,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct BinaryNode {
+    pub span: Span,
+    pub operator: BinaryOperator,
+    pub left: Box<Node>,
+    pub right: Box<Node>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct UnaryNode {
+    pub span: Span,
+    pub operator: UnaryOperator,
+    pub operand: Box<Node>,
+}
+
+/// `target = value` or `target += value`, where the target is a variable,
+/// a field or an index.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct AssignNode {
+    pub span: Span,
+    pub operator: AssignOperator,
+    pub target: Box<Node>,
+    pub value: Box<Node>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct GroupNode {
+    pub span: Span,
+    pub expression: Box<Node>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct CallNode {
+    pub span: Span,
+    pub callee: Box<Node>,
+    pub arguments: Vec<Node>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct IndexNode {
+    pub span: Span,
+    pub target: Box<Node>,
+    pub index: Box<Node>,
+}
+
+/// `object.property`, or `object?.property` which is null when the object
+/// is.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct MemberNode {
+    pub span: Span,
+    pub object: Box<Node>,
+    pub property: Box<Node>,
+    pub is_optional: bool,
+}
+
+/// Either `await expression` or `expression.await`.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct AwaitNode {
+    pub span: Span,
+    pub expression: Box<Node>,
+}
+
+/// `|a, b| a + b`, `|x: i32|: i32 { ... }` or `(a: i32): i32 => a * 2`,
+/// any of them optionally `async`.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ClosureNode {
+    pub span: Span,
+    pub is_async: bool,
+    pub parameters: Vec<Node>,
+    pub return_type: Option<Box<Node>>,
+    pub body: Box<Node>,
+}
+
+/// `condition ? consequent : alternative`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ConditionalNode {
+    pub span: Span,
+    pub condition: Box<Node>,
+    pub consequent: Box<Node>,
+    pub alternative: Box<Node>,
+}
+
+/// `start..end` or `start..=end`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct RangeNode {
+    pub span: Span,
+    pub start: Box<Node>,
+    pub end: Box<Node>,
+    pub inclusive: bool,
+}
+
+/// `match subject { pattern => body, ... }`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct MatchNode {
+    pub span: Span,
+    pub subject: Box<Node>,
+    pub arms: Vec<Node>,
+}
+
+/// `pattern if guard => body`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct MatchArmNode {
+    pub span: Span,
+    pub pattern: Box<Node>,
+    pub guard: Option<Box<Node>>,
+    pub body: Box<Node>,
+}
+
+// Patterns reuse the literal nodes for literal patterns and `IdentNode` for
+// bindings; the remaining forms have their own nodes.
+
+/// `(a, b, _)`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct TuplePatternNode {
+    pub span: Span,
+    pub elements: Vec<Node>,
+}
+
+/// `Point { x, y: 0, .. }`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct StructPatternNode {
+    pub span: Span,
+    pub path: Vec<Node>,
+    pub fields: Vec<Node>,
+    pub has_rest: bool,
+}
+
+/// A field in a struct pattern. Without a pattern, as in `Point { x }`, the
+/// field is bound to a variable of the same name.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct FieldPatternNode {
+    pub span: Span,
+    pub identifier: Box<Node>,
+    pub pattern: Option<Box<Node>>,
+}
+
+/// An enum variant such as `Shape.Circle(radius)` or `Color::Red`.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct VariantPatternNode {
+    pub span: Span,
+    pub path: Vec<Node>,
+    pub arguments: Option<Vec<Node>>,
+}
+
+/// `1..10` or `'a'..='z'`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct RangePatternNode {
+    pub span: Span,
+    pub start: Box<Node>,
+    pub end: Box<Node>,
+    pub inclusive: bool,
+}
+
+/// `a | b | c`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct OrPatternNode {
+    pub span: Span,
+    pub alternatives: Vec<Node>,
 }
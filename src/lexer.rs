@@ -1,64 +1,210 @@
 use crate::token::*;
+use unicode_normalization::UnicodeNormalization;
+use unicode_security::MixedScript;
 
 const NULL_CHAR: char = '\0';
 const ESCAPE_CHAR: char = '\\';
 const QUOTE: char = '\'';
 const DBL_QUOTE: char = '"';
-
+const BACKTICK: char = '`';
+
+/// Tokenizes source text that is fed to it either all at once with
+/// `read_source` or incrementally with `read_line`.
+///
+/// Input is accumulated into a single buffer so that token positions are
+/// absolute character offsets into everything read so far, together with
+/// the 1-based line and column on which the token starts.
+///
+/// Tokens that may span lines, such as block comments, are held back until
+/// either their closing delimiter has been read or `finish` is called to
+/// signal that no more input is coming.
 pub struct Lexer {
-    line: Vec<char>,
+    source: Vec<char>,
+    character: char,
+    position: usize,
+    read_position: usize,
+    line: usize,
+    line_start: usize,
+    finished: bool,
+    incomplete: bool,
+    // One entry per open `${` interpolation, counting the braces opened
+    // inside it so the `}` that resumes the template can be recognised.
+    templates: Vec<usize>,
+    warnings: Vec<WarningFrame>,
+}
+
+#[derive(Clone, Copy)]
+struct Checkpoint {
     character: char,
     position: usize,
     read_position: usize,
+    line: usize,
+    line_start: usize,
+}
+
+impl Default for Lexer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Lexer {
     pub fn new() -> Self {
         Self {
-            line: Vec::new(),
+            source: Vec::new(),
             position: 0,
             read_position: 0,
             character: NULL_CHAR,
+            line: 1,
+            line_start: 0,
+            finished: false,
+            incomplete: false,
+            templates: Vec::new(),
+            warnings: Vec::new(),
         }
     }
 
+    /// Appends a single line of input. A line feed is added after the line
+    /// so that tokens never run together across calls.
     pub fn read_line(&mut self, input: &str) {
-        self.line.clear();
-        self.line.extend(input.chars());
-        self.position = 0;
-        self.read_position = 0;
-        self.next_char();
+        self.source.extend(input.chars());
+        self.source.push('\n');
+        self.resume();
+    }
+
+    /// Appends a buffer of source text, such as the contents of a file.
+    pub fn read_source(&mut self, input: &str) {
+        self.source.extend(input.chars());
+        self.resume();
+    }
+
+    /// Marks the end of input. Any token still waiting on a closing
+    /// delimiter is reported as an error by the next call to `next_token`.
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    /// Returns the warnings for the tokens read since the last call.
+    pub fn take_warnings(&mut self) -> Vec<WarningFrame> {
+        std::mem::take(&mut self.warnings)
     }
 
     pub fn next_token(&mut self) -> Option<TokenFrame> {
-        if self.character == NULL_CHAR {
+        if self.is_at_end() {
             return None;
         }
 
         self.skip_whitespace();
+        let checkpoint = self.checkpoint();
         let start = self.position;
+        let line = self.line;
+        let column = self.column();
         let token = self.read_token();
         let end = self.position;
 
-        return if token == Token::EOF {
+        if self.incomplete {
+            self.incomplete = false;
+            self.restore(checkpoint);
             return None;
+        }
+
+        if let Token::Identifier(identifier) = &token {
+            if let Some(warning) = confusable_warning(identifier) {
+                self.warnings.push(WarningFrame {
+                    warning,
+                    start,
+                    end,
+                    line,
+                    column,
+                });
+            }
+        }
+
+        if token == Token::EOF {
+            None
         } else {
-            Some(TokenFrame { token, start, end })
-        };
+            Some(TokenFrame {
+                token,
+                start,
+                end,
+                line,
+                column,
+            })
+        }
+    }
+
+    /// Picks up from the end of the buffer after more input has been added.
+    fn resume(&mut self) {
+        // Reaching the end leaves no character loaded: `next_char` has set
+        // `position` to `read_position` instead of one behind it.
+        if self.position == self.read_position {
+            self.next_char();
+        }
+    }
+
+    /// Whether all input read so far has been consumed. `character` is then
+    /// `NULL_CHAR`, but a NUL in the source is an ordinary character.
+    fn is_at_end(&self) -> bool {
+        self.position >= self.source.len()
+    }
+
+    fn column(&self) -> usize {
+        self.position - self.line_start + 1
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            character: self.character,
+            position: self.position,
+            read_position: self.read_position,
+            line: self.line,
+            line_start: self.line_start,
+        }
+    }
+
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        self.character = checkpoint.character;
+        self.position = checkpoint.position;
+        self.read_position = checkpoint.read_position;
+        self.line = checkpoint.line;
+        self.line_start = checkpoint.line_start;
+    }
+
+    /// Called when the buffer runs out in the middle of a token. Before
+    /// `finish` the token is retried once more input arrives, afterwards
+    /// it is reported as `error`.
+    fn unterminated(&mut self, error: TokenError) -> Token {
+        if self.finished {
+            Token::Error(error)
+        } else {
+            self.incomplete = true;
+            Token::EOF
+        }
     }
 
     fn read_token(&mut self) -> Token {
         let current = self.character;
         let lookahead = self.peek_char();
         let token = match current {
-            NULL_CHAR => Token::EOF,
+            _ if self.is_at_end() => Token::EOF,
             '=' => match lookahead {
                 '=' => {
                     self.next_char();
                     Token::EqualTo
                 }
+                '>' => {
+                    self.next_char();
+                    Token::FatArrow
+                }
                 _ => Token::Equal,
             },
+            ':' => match lookahead {
+                ':' => {
+                    self.next_char();
+                    Token::DblColon
+                }
+                _ => Token::Colon,
+            },
             '+' => match lookahead {
                 '=' => {
                     self.next_char();
@@ -71,6 +217,10 @@ impl Lexer {
                     self.next_char();
                     Token::MinusEqual
                 }
+                '>' => {
+                    self.next_char();
+                    Token::ThinArrow
+                }
                 _ => Token::Minus,
             },
             '!' => match lookahead {
@@ -88,6 +238,9 @@ impl Lexer {
                 '/' => {
                     return self.read_single_line_comment();
                 }
+                '*' => {
+                    return self.read_multiline_comment();
+                }
                 _ => Token::FSlash,
             },
             '\\' => Token::BSlash,
@@ -103,24 +256,65 @@ impl Lexer {
                     self.next_char();
                     Token::LogicalAnd
                 }
+                '=' => {
+                    self.next_char();
+                    Token::AmpEqual
+                }
                 _ => Token::Amp,
             },
-            '%' => Token::Percent,
+            '%' => match lookahead {
+                '=' => {
+                    self.next_char();
+                    Token::PercentEqual
+                }
+                _ => Token::Percent,
+            },
             '@' => Token::At,
             '|' => match lookahead {
                 '|' => {
                     self.next_char();
                     Token::LogicalOr
                 }
+                '=' => {
+                    self.next_char();
+                    Token::PipeEqual
+                }
                 _ => Token::Pipe,
             },
-            '?' => Token::Question,
-            '^' => Token::Caret,
+            '?' => match lookahead {
+                '?' => {
+                    self.next_char();
+                    Token::DblQuestion
+                }
+                // As in TypeScript, `a?.5:1` is a conditional, not `?.`.
+                '.' if !is_digit(self.peek_second_char()) => {
+                    self.next_char();
+                    Token::QuestionDot
+                }
+                _ => Token::Question,
+            },
+            '^' => match lookahead {
+                '=' => {
+                    self.next_char();
+                    Token::CaretEqual
+                }
+                _ => Token::Caret,
+            },
             '<' => match lookahead {
                 '=' => {
                     self.next_char();
                     Token::LessThanEqual
                 }
+                '<' => {
+                    self.next_char();
+                    match self.peek_char() {
+                        '=' => {
+                            self.next_char();
+                            Token::ShiftLeftEqual
+                        }
+                        _ => Token::ShiftLeft,
+                    }
+                }
                 _ => Token::LessThan,
             },
             '>' => match lookahead {
@@ -128,19 +322,52 @@ impl Lexer {
                     self.next_char();
                     Token::GreaterThanEqual
                 }
+                '>' => {
+                    self.next_char();
+                    match self.peek_char() {
+                        '=' => {
+                            self.next_char();
+                            Token::ShiftRightEqual
+                        }
+                        _ => Token::ShiftRight,
+                    }
+                }
                 _ => Token::GreaterThan,
             },
-            '.' => Token::Dot,
+            '.' => match lookahead {
+                '.' => {
+                    self.next_char();
+                    match self.peek_char() {
+                        '=' => {
+                            self.next_char();
+                            Token::RangeInclusive
+                        }
+                        _ => Token::Range,
+                    }
+                }
+                _ => Token::Dot,
+            },
             ';' => Token::Semi,
-            ':' => Token::Colon,
             '(' => Token::LParen,
             ')' => Token::RParen,
             ',' => Token::Comma,
-            '{' => Token::LBrace,
-            '}' => Token::RBrace,
+            '{' => {
+                if let Some(depth) = self.templates.last_mut() {
+                    *depth += 1;
+                }
+                Token::LBrace
+            }
+            '}' => match self.templates.last_mut() {
+                Some(0) => return self.read_template_continuation(),
+                Some(depth) => {
+                    *depth -= 1;
+                    Token::RBrace
+                }
+                None => Token::RBrace,
+            },
             '[' => Token::LBracket,
             ']' => Token::RBracket,
-            '`' => Token::Backtick,
+            BACKTICK => return self.read_template_literal(),
             QUOTE => return self.read_char_literal(),
             DBL_QUOTE => return self.read_string_literal(),
             //
@@ -149,6 +376,11 @@ impl Lexer {
             ch => {
                 return match ch {
                     _ if is_digit(ch) => self.read_number_literal(),
+                    'r' if self.is_raw_string_start() => self.read_raw_string_literal(),
+                    'b' if lookahead == DBL_QUOTE => {
+                        self.next_char();
+                        self.read_byte_string_literal()
+                    }
                     _ if is_alpha(ch) => self.read_alpha_literal(),
                     _ => {
                         self.next_char();
@@ -163,42 +395,248 @@ impl Lexer {
     }
 
     fn read_string_literal(&mut self) -> Token {
-        let start_pos = self.position;
-        if !self.read_until_char(DBL_QUOTE) {
-            return Token::Error(TokenError::UnterminatedStringLiteral);
+        match self.read_quoted(DBL_QUOTE, false) {
+            Some(Ok(literal)) => Token::StringLiteral(literal),
+            Some(Err(error)) => Token::Error(error),
+            None => self.unterminated(TokenError::UnterminatedStringLiteral),
         }
-        let literal = self.slice_line(start_pos + 1, self.position);
+    }
+
+    /// Reads `b"..."` from its opening quote. Every character must be
+    /// ASCII and `\u` escapes are not allowed, but `\x` gives any byte.
+    fn read_byte_string_literal(&mut self) -> Token {
+        match self.read_quoted(DBL_QUOTE, true) {
+            // Each char is at most `\xFF`, so it is exactly one byte.
+            Some(Ok(literal)) => {
+                Token::ByteStringLiteral(literal.chars().map(|ch| ch as u8).collect())
+            }
+            Some(Err(error)) => Token::Error(error),
+            None => self.unterminated(TokenError::UnterminatedStringLiteral),
+        }
+    }
+
+    /// Whether the `r` under the cursor opens a raw string: `r"` or `r#"`
+    /// with any number of `#`.
+    fn is_raw_string_start(&self) -> bool {
+        let hashes = self.source[self.read_position..]
+            .iter()
+            .take_while(|ch| **ch == '#')
+            .count();
+        self.source.get(self.read_position + hashes) == Some(&DBL_QUOTE)
+    }
+
+    /// Reads `r"..."` or `r#"..."#`. Nothing is escaped; the literal ends at
+    /// the first quote followed by as many `#` as opened it.
+    fn read_raw_string_literal(&mut self) -> Token {
+        self.next_char();
+        let mut hashes = 0;
+        while self.character == '#' {
+            hashes += 1;
+            self.next_char();
+        }
+
+        let start_pos = self.position + 1;
         self.next_char();
-        Token::StringLiteral(literal)
+        while !self.is_at_end() {
+            if self.character == DBL_QUOTE && self.is_raw_string_end(hashes) {
+                let literal = self.slice_source(start_pos, self.position);
+                for _ in 0..=hashes {
+                    self.next_char();
+                }
+                return Token::StringLiteral(literal);
+            }
+            self.next_char();
+        }
+        self.unterminated(TokenError::UnterminatedStringLiteral)
+    }
+
+    fn is_raw_string_end(&self, hashes: usize) -> bool {
+        let closing = &self.source[self.read_position..];
+        closing.len() >= hashes && closing[..hashes].iter().all(|ch| *ch == '#')
     }
 
     fn read_char_literal(&mut self) -> Token {
+        let literal = match self.read_quoted(QUOTE, false) {
+            Some(Ok(literal)) => literal,
+            Some(Err(error)) => return Token::Error(error),
+            None => return Token::Error(TokenError::UnterminatedCharLiteral),
+        };
+        let mut chars = literal.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) => Token::CharLiteral(ch),
+            (None, _) => Token::Error(TokenError::EmptyCharLiteral),
+            (Some(_), Some(_)) => Token::Error(TokenError::OverlongCharLiteral),
+        }
+    }
+
+    /// Reads the body of a literal delimited by `quote`, decoding escape
+    /// sequences. Strings may run over several lines, char literals may
+    /// not. Returns `None` if the literal ends before the closing quote.
+    /// An invalid escape does not stop the scan, so the lexer still resumes
+    /// after the closing quote, but the first one found is returned. In a
+    /// byte string, `\x80` to `\xFF` are kept as the chars of the same value.
+    fn read_quoted(&mut self, quote: char, is_byte: bool) -> Option<Result<String, TokenError>> {
+        let mut literal = String::new();
+        let mut error: Option<TokenError> = None;
+        self.next_char();
+        loop {
+            match self.character {
+                _ if self.is_at_end() => return None,
+                '\n' if quote == QUOTE => return None,
+                ESCAPE_CHAR => match self.read_escape(is_byte) {
+                    Ok(ch) => literal.push(ch),
+                    Err(err) => {
+                        error.get_or_insert(err);
+                    }
+                },
+                ch if ch == quote => {
+                    self.next_char();
+                    break;
+                }
+                ch if is_byte && !ch.is_ascii() => {
+                    error.get_or_insert(TokenError::NonAsciiInByteString(ch));
+                    self.next_char();
+                }
+                ch => {
+                    literal.push(ch);
+                    self.next_char();
+                }
+            }
+        }
+        Some(match error {
+            Some(error) => Err(error),
+            None => Ok(literal),
+        })
+    }
+
+    /// Decodes the escape sequence starting at the current backslash and
+    /// leaves the lexer on the character following it.
+    fn read_escape(&mut self, is_byte: bool) -> Result<char, TokenError> {
         let start_pos = self.position;
-        if !self.read_until_char(QUOTE) {
-            return Token::Error(TokenError::UnterminatedCharLiteral);
+        self.next_char();
+        let decoded = match self.character {
+            _ if self.is_at_end() => {
+                return Err(TokenError::InvalidEscape(ESCAPE_CHAR.to_string()))
+            }
+            '\n' => return Err(TokenError::InvalidEscape(ESCAPE_CHAR.to_string())),
+            'n' => Some('\n'),
+            'r' => Some('\r'),
+            't' => Some('\t'),
+            '0' => Some('\0'),
+            ESCAPE_CHAR | QUOTE | DBL_QUOTE => Some(self.character),
+            'x' => {
+                // Limited to ASCII in text so the result is always a single
+                // byte; a byte string may hold any byte.
+                let max = if is_byte { 0xFF } else { 0x7F };
+                let digits = self.read_hex_digits(2);
+                match u32::from_str_radix(&digits, 16) {
+                    Ok(value) if digits.len() == 2 && value <= max => char::from_u32(value),
+                    _ => None,
+                }
+            }
+            'u' if !is_byte && self.peek_char() == '{' => {
+                self.next_char();
+                let digits = self.read_hex_digits(6);
+                if self.peek_char() == '}' {
+                    self.next_char();
+                    u32::from_str_radix(&digits, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        self.next_char();
+        decoded
+            .ok_or_else(|| TokenError::InvalidEscape(self.slice_source(start_pos, self.position)))
+    }
+
+    fn read_hex_digits(&mut self, max: usize) -> String {
+        let mut digits = String::new();
+        while digits.len() < max && is_hex(self.peek_char()) {
+            self.next_char();
+            digits.push(self.character);
         }
-        let literal = self.slice_line(start_pos + 1, self.position);
+        digits
+    }
+
+    fn read_template_literal(&mut self) -> Token {
+        match self.read_template_chunk() {
+            Some((chunk, false)) => Token::TemplateLiteral(chunk),
+            Some((chunk, true)) => {
+                self.templates.push(0);
+                Token::TemplateHead(chunk)
+            }
+            None => self.unterminated(TokenError::UnterminatedTemplateLiteral),
+        }
+    }
+
+    fn read_template_continuation(&mut self) -> Token {
+        match self.read_template_chunk() {
+            Some((chunk, false)) => {
+                self.templates.pop();
+                Token::TemplateTail(chunk)
+            }
+            Some((chunk, true)) => Token::TemplateMiddle(chunk),
+            None => self.unterminated(TokenError::UnterminatedTemplateLiteral),
+        }
+    }
+
+    /// Reads the raw text following an opening backtick or the `}` closing
+    /// an interpolation. Returns the text and whether it was ended by `${`
+    /// rather than a closing backtick, or `None` if input ran out first.
+    fn read_template_chunk(&mut self) -> Option<(String, bool)> {
+        let start_pos = self.position + 1;
         self.next_char();
-        Token::CharLiteral(literal)
+        while !self.is_at_end() {
+            match self.character {
+                ESCAPE_CHAR => self.next_char(),
+                BACKTICK => {
+                    let chunk = self.slice_source(start_pos, self.position);
+                    self.next_char();
+                    return Some((chunk, false));
+                }
+                '$' if self.peek_char() == '{' => {
+                    let chunk = self.slice_source(start_pos, self.position);
+                    self.next_char();
+                    self.next_char();
+                    return Some((chunk, true));
+                }
+                _ => {}
+            }
+            self.next_char();
+        }
+        None
     }
 
     fn read_number_literal(&mut self) -> Token {
         let start_pos = self.position;
         let lookahead = self.peek_char();
 
-        // Hexadecimal
-        if self.character == '0' && (lookahead == 'X' || lookahead == 'x') {
+        // Hexadecimal, binary and octal
+        let radix = match (self.character, lookahead) {
+            ('0', 'x' | 'X') => Some((NumberKind::Hexadecimal, 16)),
+            ('0', 'b' | 'B') => Some((NumberKind::Binary, 2)),
+            ('0', 'o' | 'O') => Some((NumberKind::Octal, 8)),
+            _ => None,
+        };
+        if let Some((kind, radix)) = radix {
             self.next_char();
-            self.read_while(|ch| !is_hex(ch));
-            let literal = self.slice_line(start_pos, self.position);
-            if literal.len() == 2 {
-                return Token::Error(TokenError::MalformedHexadecimal);
+            // Digits beyond the radix are read too, so that `0b102` is one
+            // malformed literal rather than `0b10` followed by `2`.
+            self.read_while(|ch| !(is_valid_number_literal_char(ch) || radix == 16 && is_hex(ch)));
+            let literal = self.slice_source(start_pos, self.position);
+            let digits: Vec<char> = literal[2..].chars().filter(|ch| *ch != '_').collect();
+            if digits.is_empty() || digits.iter().any(|ch| !ch.is_digit(radix)) {
+                return match kind {
+                    NumberKind::Hexadecimal => Token::Error(TokenError::MalformedHexadecimal),
+                    NumberKind::Binary => Token::Error(TokenError::MalformedBinary),
+                    _ => Token::Error(TokenError::MalformedOctal),
+                };
             }
-            return Token::NumberLiteral(Number {
-                kind: NumberKind::Hexadecimal,
-                value: literal,
-                postfix: None,
-            });
+            return self.read_number_suffix(kind, literal);
         }
 
         // Integer
@@ -212,38 +650,77 @@ impl Lexer {
             self.read_while(|ch| !is_valid_number_literal_char(ch));
         }
 
-        if self.character == '.' {
-            self.read_until_char(' ');
+        // A second period is only allowed as the start of a range: `1..2`
+        if self.character == '.' && self.peek_char() != '.' {
+            self.read_while(is_whitespace);
             return Token::Error(TokenError::MalformedDecimal);
         }
 
-        let number_value = self.slice_line(start_pos, self.position);
+        // Exponent: `1e10`, `1.5E-3`. Without digits after it the `e` is
+        // read as a suffix instead.
+        if matches!(self.character, 'e' | 'E') {
+            let lookahead = self.peek_char();
+            let is_exponent = is_digit(lookahead)
+                || (matches!(lookahead, '+' | '-') && is_digit(self.peek_second_char()));
+            if is_exponent {
+                number_kind = NumberKind::Decimal;
+                self.next_char();
+                if matches!(self.character, '+' | '-') {
+                    self.next_char();
+                }
+                self.read_while(|ch| !is_valid_number_literal_char(ch));
+            }
+        }
+
+        let number_value = self.slice_source(start_pos, self.position);
+        self.read_number_suffix(number_kind, number_value)
+    }
 
-        // Postfix
-        let mut postfix_opt: Option<String> = None;
-        if is_alpha(self.character) {
-            let postfix_start = self.position;
-            self.read_while(|ch| !is_alpha(ch));
-            let postfix_str = self.slice_line(postfix_start, self.position);
-            postfix_opt = Some(postfix_str);
+    /// Reads the type suffix, if any, that follows the digits of a number.
+    /// Floats only take `f32` and `f64`, and hexadecimal, binary and octal
+    /// literals only take the integer suffixes.
+    fn read_number_suffix(&mut self, kind: NumberKind, value: String) -> Token {
+        if !is_alpha(self.character) {
+            return Token::NumberLiteral(Number {
+                kind,
+                value,
+                postfix: None,
+            });
         }
 
-        Token::NumberLiteral(Number {
-            kind: number_kind,
-            value: number_value,
-            postfix: postfix_opt,
-        })
+        let suffix_start = self.position;
+        self.read_while(|ch| !is_valid_ident_literal_char(ch));
+        let name = self.slice_source(suffix_start, self.position);
+
+        let postfix = NumberSuffix::from_name(&name).filter(|suffix| match kind {
+            NumberKind::Integer => true,
+            NumberKind::Decimal => suffix.is_float(),
+            _ => !suffix.is_float(),
+        });
+        match postfix {
+            Some(postfix) => Token::NumberLiteral(Number {
+                kind,
+                value,
+                postfix: Some(postfix),
+            }),
+            None => Token::Error(TokenError::InvalidNumberSuffix(name)),
+        }
     }
 
     fn read_alpha_literal(&mut self) -> Token {
         let start_pos = self.position;
-        while self.character != NULL_CHAR {
+        while !self.is_at_end() {
             self.next_char();
             if !is_valid_ident_literal_char(self.character) {
                 break;
             }
         }
-        let literal = self.slice_line(start_pos, self.position);
+        let mut literal = self.slice_source(start_pos, self.position);
+        if !literal.is_ascii() {
+            // `é` may be written as one code point or as `e` followed by a
+            // combining accent; both spell the same name.
+            literal = literal.nfc().collect();
+        }
         match literal.as_str() {
             "true" => Token::BoolLiteral(true),
             "false" => Token::BoolLiteral(false),
@@ -257,6 +734,7 @@ impl Lexer {
             "for" => Token::Keyword(Keyword::FOR),
             "func" => Token::Keyword(Keyword::FUNC),
             "if" => Token::Keyword(Keyword::IF),
+            "in" => Token::Keyword(Keyword::IN),
             "impl" => Token::Keyword(Keyword::IMPL),
             "let" => Token::Keyword(Keyword::LET),
             "match" => Token::Keyword(Keyword::MATCH),
@@ -275,31 +753,53 @@ impl Lexer {
 
     fn read_single_line_comment(&mut self) -> Token {
         let start_pos = self.position;
-        self.read_until_end();
-        let literal = self.slice_line(start_pos, self.read_position);
-        Token::Comment(literal)
-    }
-
-    fn slice_line(&self, start_pos: usize, end_pos: usize) -> String {
-        let end_pos_min = end_pos.min(self.line.len());
-        let sliced_chars = &self.line[start_pos..end_pos_min];
-        sliced_chars.iter().collect()
+        self.read_until_end_of_line();
+        let literal = self.slice_source(start_pos, self.position);
+        // `////` and beyond is a plain comment, often used as a separator.
+        if literal.starts_with("///") && !literal.starts_with("////") {
+            Token::DocComment(literal)
+        } else {
+            Token::Comment(literal)
+        }
     }
 
-    fn read_until_char(&mut self, stop_char: char) -> bool {
-        let mut last_char: char;
-        while self.character != NULL_CHAR {
-            last_char = self.character;
-            self.next_char();
-            if self.character == stop_char && last_char != ESCAPE_CHAR {
-                return true;
+    fn read_multiline_comment(&mut self) -> Token {
+        let start_pos = self.position;
+        let mut depth = 0;
+        while !self.is_at_end() {
+            let lookahead = self.peek_char();
+            if self.character == '/' && lookahead == '*' {
+                depth += 1;
+                self.next_char();
+            } else if self.character == '*' && lookahead == '/' {
+                depth -= 1;
+                self.next_char();
+                if depth == 0 {
+                    self.next_char();
+                    let literal = self.slice_source(start_pos, self.position);
+                    let is_doc = literal.starts_with("/**")
+                        && !literal.starts_with("/***")
+                        && literal != "/**/";
+                    return if is_doc {
+                        Token::DocComment(literal)
+                    } else {
+                        Token::MultilineComment(literal)
+                    };
+                }
             }
+            self.next_char();
         }
-        return false;
+        self.unterminated(TokenError::UnterminatedMultilineComment)
+    }
+
+    fn slice_source(&self, start_pos: usize, end_pos: usize) -> String {
+        let end_pos_min = end_pos.min(self.source.len());
+        let sliced_chars = &self.source[start_pos..end_pos_min];
+        sliced_chars.iter().collect()
     }
 
-    fn read_until_end(&mut self) {
-        while self.character != NULL_CHAR {
+    fn read_until_end_of_line(&mut self) {
+        while !self.is_at_end() && self.character != '\n' {
             self.next_char();
         }
     }
@@ -308,7 +808,7 @@ impl Lexer {
     where
         F: FnMut(char) -> bool,
     {
-        while self.character != NULL_CHAR {
+        while !self.is_at_end() {
             self.next_char();
             if condition(self.character) {
                 break;
@@ -317,8 +817,12 @@ impl Lexer {
     }
 
     fn next_char(&mut self) {
-        if self.read_position < self.line.len() {
-            let ch = self.line[self.read_position];
+        if self.character == '\n' {
+            self.line += 1;
+            self.line_start = self.read_position;
+        }
+        if self.read_position < self.source.len() {
+            let ch = self.source[self.read_position];
             self.position = self.read_position;
             self.read_position += 1;
             self.character = ch;
@@ -328,45 +832,82 @@ impl Lexer {
         }
     }
 
-    fn peek_char(&mut self) -> char {
-        if self.read_position < self.line.len() {
-            self.line[self.read_position]
+    fn peek_char(&self) -> char {
+        if self.read_position < self.source.len() {
+            self.source[self.read_position]
         } else {
             NULL_CHAR
         }
     }
 
+    /// The character after the one `peek_char` returns.
+    fn peek_second_char(&self) -> char {
+        match self.source.get(self.read_position + 1) {
+            Some(ch) => *ch,
+            None => NULL_CHAR,
+        }
+    }
+
     fn skip_whitespace(&mut self) {
         while is_whitespace(self.character) {
             self.next_char();
-            if self.character == NULL_CHAR {
+            if self.is_at_end() {
                 break;
             }
         }
     }
 }
 
+/// Unicode White_Space, such as no-break and ideographic spaces, plus the
+/// left-to-right and right-to-left marks from Pattern_White_Space.
 fn is_whitespace(ch: char) -> bool {
-    if ch == ' ' || ch == '\t' || ch == '\n' || ch == '\r' {
-        return true;
-    }
-    return false;
+    ch.is_whitespace() || ch == '\u{200E}' || ch == '\u{200F}'
 }
 
+/// Whether `ch` can start an identifier, per UAX #31.
 fn is_alpha(ch: char) -> bool {
-    'a' <= ch && ch <= 'z' || 'A' <= ch && ch <= 'Z' || ch == '_'
+    unicode_ident::is_xid_start(ch) || ch == '_'
 }
 
 fn is_digit(ch: char) -> bool {
-    '0' <= ch && ch <= '9'
+    ch.is_ascii_digit()
 }
 
 fn is_hex(ch: char) -> bool {
-    is_digit(ch) || (ch >= 'a' && ch <= 'f') || (ch >= 'A' && ch <= 'F')
+    ch.is_ascii_hexdigit()
 }
 
 fn is_valid_ident_literal_char(ch: char) -> bool {
-    is_alpha(ch) || is_digit(ch)
+    unicode_ident::is_xid_continue(ch)
+}
+
+/// Warns about an identifier that may be imitating another one. A name
+/// written wholly in one non-Latin script is left alone unless every
+/// character in it looks like ASCII, so localized names stay quiet.
+fn confusable_warning(identifier: &str) -> Option<TokenWarning> {
+    if identifier.is_ascii() {
+        return None;
+    }
+
+    let looks_like_ascii = |ch: char| -> Option<String> {
+        let prototype: String = unicode_security::skeleton(&ch.to_string()).collect();
+        (prototype.is_ascii() && !ch.is_ascii()).then_some(prototype)
+    };
+
+    let imitates_ascii = identifier
+        .chars()
+        .all(|ch| ch.is_ascii() || looks_like_ascii(ch).is_some());
+    if identifier.is_single_script() && !imitates_ascii {
+        return None;
+    }
+
+    identifier.chars().find_map(|character| {
+        looks_like_ascii(character).map(|looks_like| TokenWarning::ConfusableCharacter {
+            identifier: identifier.to_string(),
+            character,
+            looks_like,
+        })
+    })
 }
 
 fn is_valid_number_literal_char(ch: char) -> bool {
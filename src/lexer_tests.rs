@@ -5,6 +5,7 @@ fn read_tokens(input: &str) -> Vec<TokenFrame> {
     let mut lexer = Lexer::new();
     let mut frames: Vec<TokenFrame> = Vec::new();
     lexer.read_line(input);
+    lexer.finish();
 
     while let Some(frame) = lexer.next_token() {
         frames.push(frame);
@@ -94,6 +95,58 @@ fn test_spans() {
     assert_span(r#" "string""#, 1, 9);
 }
 
+fn assert_position(frame: &TokenFrame, start: usize, line: usize, column: usize) {
+    assert_eq!(
+        (frame.start, frame.line, frame.column),
+        (start, line, column),
+        "Expected {:?} at offset {} ({}:{}) but was at {} ({}:{})",
+        frame.token,
+        start,
+        line,
+        column,
+        frame.start,
+        frame.line,
+        frame.column
+    );
+}
+
+#[test]
+fn test_positions_across_lines() {
+    let mut lexer = Lexer::new();
+    let mut frames: Vec<TokenFrame> = Vec::new();
+    for line in ["let x = 1;", "", "  y"] {
+        lexer.read_line(line);
+        while let Some(frame) = lexer.next_token() {
+            frames.push(frame);
+        }
+    }
+
+    assert_eq!(frames.len(), 6);
+    assert_position(&frames[0], 0, 1, 1);
+    assert_position(&frames[3], 8, 1, 9);
+    assert_position(&frames[4], 9, 1, 10);
+    assert_position(&frames[5], 14, 3, 3);
+    assert_eq!(frames[5].end, 15);
+}
+
+#[test]
+fn test_positions_in_source() {
+    let mut lexer = Lexer::new();
+    let mut frames: Vec<TokenFrame> = Vec::new();
+    lexer.read_source("// one\n\tfoo\n\n bar // two\nbaz");
+    while let Some(frame) = lexer.next_token() {
+        frames.push(frame);
+    }
+
+    assert_eq!(frames.len(), 5);
+    assert_eq!(frames[0].token, Token::Comment("// one".to_string()));
+    assert_position(&frames[0], 0, 1, 1);
+    assert_position(&frames[1], 8, 2, 2);
+    assert_position(&frames[2], 14, 4, 2);
+    assert_position(&frames[3], 18, 4, 6);
+    assert_position(&frames[4], 25, 5, 1);
+}
+
 #[test]
 fn test_illegal_char() {
     assert_token("✓", Token::Error(TokenError::Illegal('✓')));
@@ -101,6 +154,16 @@ fn test_illegal_char() {
     assert_token("☒", Token::Error(TokenError::Illegal('☒')));
     assert_token("“", Token::Error(TokenError::Illegal('“')));
     assert_token("”", Token::Error(TokenError::Illegal('”')));
+    assert_tokens(
+        // a NUL in the source does not end the input
+        "a\0b",
+        vec![
+            Token::Identifier("a".to_string()),
+            Token::Error(TokenError::Illegal('\0')),
+            Token::Identifier("b".to_string()),
+        ],
+    );
+    assert_tokens("\"a\0b\"", vec![Token::StringLiteral("a\0b".to_string())]);
 }
 
 #[test]
@@ -120,7 +183,7 @@ fn test_operators_and_delimiters() {
     assert_token("|", Token::Pipe);
     assert_token("+", Token::Plus);
     assert_token("?", Token::Question);
-    assert_token("`", Token::Backtick);
+    assert_token("`", Token::Error(TokenError::UnterminatedTemplateLiteral));
     assert_token(":", Token::Colon);
     assert_token(",", Token::Comma);
     assert_token(".", Token::Dot);
@@ -132,6 +195,7 @@ fn test_operators_and_delimiters() {
     assert_token(")", Token::RParen);
     assert_token(";", Token::Semi);
     assert_token("==", Token::EqualTo);
+    assert_token("=>", Token::FatArrow);
     assert_token("+=", Token::PlusEqual);
     assert_token("-=", Token::MinusEqual);
     assert_token("/=", Token::DivideEqual);
@@ -142,6 +206,181 @@ fn test_operators_and_delimiters() {
     assert_token("-=", Token::MinusEqual);
     assert_token("*=", Token::MultiplyEqual);
     assert_token("!=", Token::NotEqualTo);
+    assert_token("%=", Token::PercentEqual);
+    assert_token("&=", Token::AmpEqual);
+    assert_token("|=", Token::PipeEqual);
+    assert_token("^=", Token::CaretEqual);
+    assert_token("<<=", Token::ShiftLeftEqual);
+    assert_token(">>=", Token::ShiftRightEqual);
+    assert_token("..", Token::Range);
+    assert_token("..=", Token::RangeInclusive);
+}
+
+#[test]
+fn test_fat_arrow() {
+    assert_tokens("==>", vec![Token::EqualTo, Token::GreaterThan]);
+    assert_tokens("=>=", vec![Token::FatArrow, Token::Equal]);
+    assert_tokens(
+        "_ => x",
+        vec![
+            Token::Identifier("_".to_string()),
+            Token::FatArrow,
+            Token::Identifier("x".to_string()),
+        ],
+    );
+}
+
+#[test]
+fn test_path_separator() {
+    assert_tokens(
+        "a::b",
+        vec![
+            Token::Identifier("a".to_string()),
+            Token::DblColon,
+            Token::Identifier("b".to_string()),
+        ],
+    );
+    assert_tokens(":::", vec![Token::DblColon, Token::Colon]);
+    assert_tokens(": :", vec![Token::Colon, Token::Colon]);
+}
+
+#[test]
+fn test_compound_assignment() {
+    assert_tokens(
+        "a <<= 1",
+        vec![
+            Token::Identifier("a".to_string()),
+            Token::ShiftLeftEqual,
+            Token::NumberLiteral(Number {
+                kind: NumberKind::Integer,
+                value: "1".to_string(),
+                postfix: None,
+            }),
+        ],
+    );
+    assert_tokens("< <=", vec![Token::LessThan, Token::LessThanEqual]);
+    assert_tokens("> >=", vec![Token::GreaterThan, Token::GreaterThanEqual]);
+    assert_tokens("&&=", vec![Token::LogicalAnd, Token::Equal]);
+    assert_tokens("||=", vec![Token::LogicalOr, Token::Equal]);
+}
+
+#[test]
+fn test_multi_character_operators() {
+    assert_token("->", Token::ThinArrow);
+    assert_token("=>", Token::FatArrow);
+    assert_token("::", Token::DblColon);
+    assert_token("..", Token::Range);
+    assert_token("..=", Token::RangeInclusive);
+    assert_token("<<", Token::ShiftLeft);
+    assert_token(">>", Token::ShiftRight);
+    assert_token("?.", Token::QuestionDot);
+    assert_token("??", Token::DblQuestion);
+
+    // The longest operator wins, and what is left starts the next token.
+    assert_tokens("<<<", vec![Token::ShiftLeft, Token::LessThan]);
+    assert_tokens(">>>=", vec![Token::ShiftRight, Token::GreaterThanEqual]);
+    assert_tokens("<<==", vec![Token::ShiftLeftEqual, Token::Equal]);
+    assert_tokens("->>", vec![Token::ThinArrow, Token::GreaterThan]);
+    assert_tokens("-->", vec![Token::Minus, Token::ThinArrow]);
+    assert_tokens("???", vec![Token::DblQuestion, Token::Question]);
+    assert_tokens("??.", vec![Token::DblQuestion, Token::Dot]);
+    assert_tokens("?..", vec![Token::QuestionDot, Token::Dot]);
+    assert_tokens("...", vec![Token::Range, Token::Dot]);
+    assert_tokens("..==", vec![Token::RangeInclusive, Token::Equal]);
+    assert_tokens(":::", vec![Token::DblColon, Token::Colon]);
+    assert_tokens("=>>", vec![Token::FatArrow, Token::GreaterThan]);
+
+    // `?.` followed by a digit is a `?` and a `.`, as in TypeScript.
+    assert_tokens(
+        "?.5",
+        vec![
+            Token::Question,
+            Token::Dot,
+            Token::NumberLiteral(Number {
+                kind: NumberKind::Integer,
+                value: "5".to_string(),
+                postfix: None,
+            }),
+        ],
+    );
+}
+
+#[test]
+fn test_multi_character_operator_spans() {
+    let frames = read_tokens("a?.b ?? c::d -> x << 2 >> 1..=3");
+    let spans: Vec<(Token, usize, usize)> = frames
+        .into_iter()
+        .map(|frame| (frame.token, frame.start, frame.end))
+        .collect();
+    let ident = |name: &str| Token::Identifier(name.to_string());
+    let integer = |value: &str| {
+        Token::NumberLiteral(Number {
+            kind: NumberKind::Integer,
+            value: value.to_string(),
+            postfix: None,
+        })
+    };
+    assert_eq!(
+        spans,
+        vec![
+            (ident("a"), 0, 1),
+            (Token::QuestionDot, 1, 3),
+            (ident("b"), 3, 4),
+            (Token::DblQuestion, 5, 7),
+            (ident("c"), 8, 9),
+            (Token::DblColon, 9, 11),
+            (ident("d"), 11, 12),
+            (Token::ThinArrow, 13, 15),
+            (ident("x"), 16, 17),
+            (Token::ShiftLeft, 18, 20),
+            (integer("2"), 21, 22),
+            (Token::ShiftRight, 23, 25),
+            (integer("1"), 26, 27),
+            (Token::RangeInclusive, 27, 30),
+            (integer("3"), 30, 31),
+        ]
+    );
+
+    assert_span("<<=", 0, 3);
+    assert_span(">>=", 0, 3);
+    assert_span("->", 0, 2);
+}
+
+#[test]
+fn test_ranges() {
+    let integer = |value: &str| {
+        Token::NumberLiteral(Number {
+            kind: NumberKind::Integer,
+            value: value.to_string(),
+            postfix: None,
+        })
+    };
+    assert_tokens("0..10", vec![integer("0"), Token::Range, integer("10")]);
+    assert_tokens(
+        "0..=10",
+        vec![integer("0"), Token::RangeInclusive, integer("10")],
+    );
+    assert_tokens(
+        "1.5..2",
+        vec![
+            Token::NumberLiteral(Number {
+                kind: NumberKind::Decimal,
+                value: "1.5".to_string(),
+                postfix: None,
+            }),
+            Token::Range,
+            integer("2"),
+        ],
+    );
+    assert_tokens(
+        "a...b",
+        vec![
+            Token::Identifier("a".to_string()),
+            Token::Range,
+            Token::Dot,
+            Token::Identifier("b".to_string()),
+        ],
+    );
 }
 
 #[test]
@@ -149,12 +388,22 @@ fn test_char_literal() {
     assert_token(
         // basic char
         "'c'",
-        Token::CharLiteral("c".to_string()),
+        Token::CharLiteral('c'),
     );
     assert_token(
         // empty char
         "''",
-        Token::CharLiteral("".to_string()),
+        Token::Error(TokenError::EmptyCharLiteral),
+    );
+    assert_token(
+        // more than one char
+        "'ab'",
+        Token::Error(TokenError::OverlongCharLiteral),
+    );
+    assert_token(
+        // non-ascii char
+        "'☺'",
+        Token::CharLiteral('☺'),
     );
     assert_token(
         // unterminated char
@@ -164,7 +413,22 @@ fn test_char_literal() {
     assert_token(
         // escaped quote
         r#"'\''"#,
-        Token::CharLiteral(r#"\'"#.to_string()),
+        Token::CharLiteral('\''),
+    );
+    assert_token(
+        // escaped backslash
+        r#"'\\'"#,
+        Token::CharLiteral('\\'),
+    );
+    assert_token(
+        // unicode escape
+        r#"'\u{1F600}'"#,
+        Token::CharLiteral('😀'),
+    );
+    assert_token(
+        // two escapes
+        r#"'\n\t'"#,
+        Token::Error(TokenError::OverlongCharLiteral),
     );
 }
 
@@ -183,7 +447,186 @@ fn test_string_literal() {
     assert_token(
         // escaped double quotes
         r#""\"Make it so\"""#,
-        Token::StringLiteral(r#"\"Make it so\""#.to_string()),
+        Token::StringLiteral(r#""Make it so""#.to_string()),
+    );
+    assert_token(
+        // trailing escaped backslash
+        r#""a\\""#,
+        Token::StringLiteral(r#"a\"#.to_string()),
+    );
+    assert_token(
+        // unterminated
+        r#""a\""#,
+        Token::Error(TokenError::UnterminatedStringLiteral),
+    );
+}
+
+#[test]
+fn test_raw_string_literal() {
+    assert_token(
+        r#"r"C:\path\n""#,
+        Token::StringLiteral(r"C:\path\n".to_string()),
+    );
+    assert_token(r#"r"""#, Token::StringLiteral("".to_string()));
+    assert_token(
+        r##"r#"say "hi""#"##,
+        Token::StringLiteral(r#"say "hi""#.to_string()),
+    );
+    assert_token(
+        // a closing quote with too few hashes is part of the text
+        r###"r##"a"# b"##"###,
+        Token::StringLiteral(r##"a"# b"##.to_string()),
+    );
+    assert_tokens(
+        // not a raw string
+        "r #",
+        vec![
+            Token::Identifier("r".to_string()),
+            Token::Error(TokenError::Illegal('#')),
+        ],
+    );
+    assert_token(
+        r##"r#"open"##,
+        Token::Error(TokenError::UnterminatedStringLiteral),
+    );
+}
+
+#[test]
+fn test_byte_string_literal() {
+    assert_token(
+        r#"b"hi\n\x7F""#,
+        Token::ByteStringLiteral(b"hi\n\x7F".to_vec()),
+    );
+    assert_token(r#"b"""#, Token::ByteStringLiteral(Vec::new()));
+    assert_token(
+        r#"b"\x00\x80\xFF""#,
+        Token::ByteStringLiteral(vec![0x00, 0x80, 0xFF]),
+    );
+    assert_token(
+        r#""\xFF""#,
+        Token::Error(TokenError::InvalidEscape("\\xFF".to_string())),
+    );
+    assert_token(
+        r#"b"\u{e9}""#,
+        Token::Error(TokenError::InvalidEscape("\\u".to_string())),
+    );
+    assert_token(
+        r#"b"café""#,
+        Token::Error(TokenError::NonAsciiInByteString('é')),
+    );
+    assert_tokens(
+        "b + c",
+        vec![
+            Token::Identifier("b".to_string()),
+            Token::Plus,
+            Token::Identifier("c".to_string()),
+        ],
+    );
+}
+
+#[test]
+fn test_string_literal_across_lines() {
+    let mut lexer = Lexer::new();
+    let mut frames: Vec<TokenFrame> = Vec::new();
+    for line in ["x = \"first", "second", "third\"; r#\"raw", "\"# y"] {
+        lexer.read_line(line);
+        while let Some(frame) = lexer.next_token() {
+            frames.push(frame);
+        }
+    }
+
+    let tokens: Vec<Token> = frames.iter().map(|frame| frame.token.clone()).collect();
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Identifier("x".to_string()),
+            Token::Equal,
+            Token::StringLiteral("first\nsecond\nthird".to_string()),
+            Token::Semi,
+            Token::StringLiteral("raw\n".to_string()),
+            Token::Identifier("y".to_string()),
+        ]
+    );
+    assert_position(&frames[2], 4, 1, 5);
+    assert_eq!(frames[2].end, 24);
+    assert_position(&frames[3], 24, 3, 7);
+    assert_position(&frames[4], 26, 3, 9);
+    assert_position(&frames[5], 36, 4, 4);
+}
+
+#[test]
+fn test_string_literal_waits_for_input() {
+    let mut lexer = Lexer::new();
+    lexer.read_line("\"still open");
+    assert_eq!(lexer.next_token(), None);
+
+    lexer.finish();
+    let frame = lexer.next_token().expect("expected an error token");
+    assert_eq!(
+        frame.token,
+        Token::Error(TokenError::UnterminatedStringLiteral)
+    );
+    assert_position(&frame, 0, 1, 1);
+}
+
+#[test]
+fn test_escape_sequences() {
+    assert_token(
+        r#""\n\r\t\\\"\'\0""#,
+        Token::StringLiteral("\n\r\t\\\"'\0".to_string()),
+    );
+    assert_token(r#""\x41\x7F""#, Token::StringLiteral("A\x7F".to_string()));
+    assert_token(
+        r#""\u{48}\u{1F600}\u{10FFFF}""#,
+        Token::StringLiteral("H😀\u{10FFFF}".to_string()),
+    );
+    assert_token(
+        // unknown escape
+        r#""\q""#,
+        Token::Error(TokenError::InvalidEscape(r#"\q"#.to_string())),
+    );
+    assert_token(
+        // hex escape outside of ascii
+        r#""\x80""#,
+        Token::Error(TokenError::InvalidEscape(r#"\x80"#.to_string())),
+    );
+    assert_token(
+        // short hex escape
+        r#""\x4""#,
+        Token::Error(TokenError::InvalidEscape(r#"\x4"#.to_string())),
+    );
+    assert_token(
+        // unicode escape without braces
+        r#""\u1F600""#,
+        Token::Error(TokenError::InvalidEscape(r#"\u"#.to_string())),
+    );
+    assert_token(
+        // empty unicode escape
+        r#""\u{}""#,
+        Token::Error(TokenError::InvalidEscape(r#"\u{}"#.to_string())),
+    );
+    assert_token(
+        // surrogate
+        r#""\u{D800}""#,
+        Token::Error(TokenError::InvalidEscape(r#"\u{D800}"#.to_string())),
+    );
+    assert_token(
+        // out of range
+        r#""\u{110000}""#,
+        Token::Error(TokenError::InvalidEscape(r#"\u{110000}"#.to_string())),
+    );
+    assert_tokens(
+        // lexing resumes after the closing quote
+        r#""\q" + "ok""#,
+        vec![
+            Token::Error(TokenError::InvalidEscape(r#"\q"#.to_string())),
+            Token::Plus,
+            Token::StringLiteral("ok".to_string()),
+        ],
+    );
+    assert_token(
+        r#"'\z'"#,
+        Token::Error(TokenError::InvalidEscape(r#"\z"#.to_string())),
     );
 }
 
@@ -329,21 +772,21 @@ fn test_number_literals() {
     );
     assert_token(
         // with postfix
-        "123f",
+        "123u8",
         Token::NumberLiteral(Number {
             kind: NumberKind::Integer,
             value: "123".to_string(),
-            postfix: Some("f".to_string()),
+            postfix: Some(NumberSuffix::U8),
         }),
     );
     assert_token(
-        // with invalid postfix (parser error)
+        // with invalid postfix
         "123abc",
-        Token::NumberLiteral(Number {
-            kind: NumberKind::Integer,
-            value: "123".to_string(),
-            postfix: Some("abc".to_string()),
-        }),
+        Token::Error(TokenError::InvalidNumberSuffix("abc".to_string())),
+    );
+    assert_token(
+        "123f",
+        Token::Error(TokenError::InvalidNumberSuffix("f".to_string())),
     );
     assert_tokens(
         // with legal postfix character
@@ -371,6 +814,147 @@ fn test_number_literals() {
     );
 }
 
+fn number(kind: NumberKind, value: &str, postfix: Option<NumberSuffix>) -> Token {
+    Token::NumberLiteral(Number {
+        kind,
+        value: value.to_string(),
+        postfix,
+    })
+}
+
+#[test]
+fn test_radix_literals() {
+    assert_token("0b1010", number(NumberKind::Binary, "0b1010", None));
+    assert_token(
+        "0B1111_0000",
+        number(NumberKind::Binary, "0B1111_0000", None),
+    );
+    assert_token("0o755", number(NumberKind::Octal, "0o755", None));
+    assert_token("0xFF_FF", number(NumberKind::Hexadecimal, "0xFF_FF", None));
+    assert_token(
+        "0xFFu8",
+        number(NumberKind::Hexadecimal, "0xFF", Some(NumberSuffix::U8)),
+    );
+    assert_token(
+        "0b1i64",
+        number(NumberKind::Binary, "0b1", Some(NumberSuffix::I64)),
+    );
+    assert_token("0b", Token::Error(TokenError::MalformedBinary));
+    assert_token("0b_", Token::Error(TokenError::MalformedBinary));
+    assert_token("0b102", Token::Error(TokenError::MalformedBinary));
+    assert_token("0o8", Token::Error(TokenError::MalformedOctal));
+    assert_token("0x_", Token::Error(TokenError::MalformedHexadecimal));
+    assert_tokens(
+        "0x;",
+        vec![Token::Error(TokenError::MalformedHexadecimal), Token::Semi],
+    );
+    assert_tokens(
+        "0b)",
+        vec![Token::Error(TokenError::MalformedBinary), Token::RParen],
+    );
+    assert_tokens(
+        "0x\n1",
+        vec![
+            Token::Error(TokenError::MalformedHexadecimal),
+            number(NumberKind::Integer, "1", None),
+        ],
+    );
+    assert_span("0x;", 0, 2);
+    assert_token(
+        "0b1f32",
+        Token::Error(TokenError::InvalidNumberSuffix("f32".to_string())),
+    );
+}
+
+#[test]
+fn test_exponent_literals() {
+    assert_token("1e10", number(NumberKind::Decimal, "1e10", None));
+    assert_token("1.5e-3", number(NumberKind::Decimal, "1.5e-3", None));
+    assert_token("2E+8", number(NumberKind::Decimal, "2E+8", None));
+    assert_token(
+        "1e3f32",
+        number(NumberKind::Decimal, "1e3", Some(NumberSuffix::F32)),
+    );
+    assert_token(
+        "1e",
+        Token::Error(TokenError::InvalidNumberSuffix("e".to_string())),
+    );
+    assert_tokens(
+        "1e-x",
+        vec![
+            Token::Error(TokenError::InvalidNumberSuffix("e".to_string())),
+            Token::Minus,
+            Token::Identifier("x".to_string()),
+        ],
+    );
+}
+
+#[test]
+fn test_number_suffixes() {
+    for (name, suffix) in [
+        ("i8", NumberSuffix::I8),
+        ("i16", NumberSuffix::I16),
+        ("i32", NumberSuffix::I32),
+        ("i64", NumberSuffix::I64),
+        ("i128", NumberSuffix::I128),
+        ("isize", NumberSuffix::Isize),
+        ("u8", NumberSuffix::U8),
+        ("u16", NumberSuffix::U16),
+        ("u32", NumberSuffix::U32),
+        ("u64", NumberSuffix::U64),
+        ("u128", NumberSuffix::U128),
+        ("usize", NumberSuffix::Usize),
+        ("f32", NumberSuffix::F32),
+        ("f64", NumberSuffix::F64),
+    ] {
+        assert_token(
+            &format!("7{}", name),
+            number(NumberKind::Integer, "7", Some(suffix)),
+        );
+    }
+    assert_token(
+        "1.5f64",
+        number(NumberKind::Decimal, "1.5", Some(NumberSuffix::F64)),
+    );
+    assert_token(
+        "1.5i32",
+        Token::Error(TokenError::InvalidNumberSuffix("i32".to_string())),
+    );
+    assert_token(
+        "12banana",
+        Token::Error(TokenError::InvalidNumberSuffix("banana".to_string())),
+    );
+}
+
+#[test]
+fn test_number_values() {
+    let value = |input: &str| match &read_tokens(input)[0].token {
+        Token::NumberLiteral(number) => number.parse_value(),
+        other => panic!("Expected a number from '{}' but got {:?}", input, other),
+    };
+    assert_eq!(value("1_000"), Some(NumberValue::Integer(1000)));
+    assert_eq!(value("0xff"), Some(NumberValue::Integer(255)));
+    assert_eq!(value("0b1010"), Some(NumberValue::Integer(10)));
+    assert_eq!(value("0o755"), Some(NumberValue::Integer(493)));
+    assert_eq!(value("1.5e-3"), Some(NumberValue::Float(0.0015)));
+    assert_eq!(value("3f32"), Some(NumberValue::Float(3.0)));
+    assert_eq!(
+        value("340282366920938463463374607431768211455"),
+        Some(NumberValue::Integer(u128::MAX))
+    );
+    assert_eq!(value("340282366920938463463374607431768211456"), None);
+    assert_eq!(value("1e400"), None);
+
+    assert!(NumberSuffix::U8.fits(NumberValue::Integer(255), false));
+    assert!(!NumberSuffix::U8.fits(NumberValue::Integer(256), false));
+    assert!(NumberSuffix::U8.fits(NumberValue::Integer(0), true));
+    assert!(!NumberSuffix::U8.fits(NumberValue::Integer(1), true));
+    assert!(NumberSuffix::I8.fits(NumberValue::Integer(128), true));
+    assert!(!NumberSuffix::I8.fits(NumberValue::Integer(128), false));
+    assert!(NumberSuffix::I128.fits(NumberValue::Integer(1 << 127), true));
+    assert!(!NumberSuffix::F32.fits(NumberValue::Float(1e39), false));
+}
+
 #[test]
 fn test_keywords() {
     assert_keyword("async", Keyword::ASYNC);
@@ -383,6 +967,7 @@ fn test_keywords() {
     assert_keyword("func", Keyword::FUNC);
     assert_keyword("if", Keyword::IF);
     assert_keyword("impl", Keyword::IMPL);
+    assert_keyword("in", Keyword::IN);
     assert_keyword("let", Keyword::LET);
     assert_keyword("match", Keyword::MATCH);
     assert_keyword("pub", Keyword::PUB);
@@ -400,3 +985,271 @@ fn test_keywords() {
 fn test_comment() {
     assert_token("// comment", Token::Comment("// comment".to_string()));
 }
+
+#[test]
+fn test_doc_comment() {
+    assert_token("/// docs", Token::DocComment("/// docs".to_string()));
+    assert_token("///", Token::DocComment("///".to_string()));
+    assert_token("/** docs */", Token::DocComment("/** docs */".to_string()));
+    assert_token(
+        "/** outer /* inner */ */",
+        Token::DocComment("/** outer /* inner */ */".to_string()),
+    );
+    // Separators rather than docs
+    assert_token("//// line", Token::Comment("//// line".to_string()));
+    assert_token(
+        "/*** box ***/",
+        Token::MultilineComment("/*** box ***/".to_string()),
+    );
+    assert_tokens(
+        "/// first\n/// second",
+        vec![
+            Token::DocComment("/// first".to_string()),
+            Token::DocComment("/// second".to_string()),
+        ],
+    );
+}
+
+#[test]
+fn test_multiline_comment() {
+    assert_token("/**/", Token::MultilineComment("/**/".to_string()));
+    assert_token(
+        "/* comment */",
+        Token::MultilineComment("/* comment */".to_string()),
+    );
+    assert_token(
+        // nested
+        "/* outer /* inner */ still outer */",
+        Token::MultilineComment("/* outer /* inner */ still outer */".to_string()),
+    );
+    assert_tokens(
+        // closing delimiter is not shared with the opening one
+        "/*/ x */ y",
+        vec![
+            Token::MultilineComment("/*/ x */".to_string()),
+            Token::Identifier("y".to_string()),
+        ],
+    );
+    assert_token(
+        // unterminated
+        "/* comment",
+        Token::Error(TokenError::UnterminatedMultilineComment),
+    );
+    assert_token(
+        // unterminated nested
+        "/* outer /* inner */",
+        Token::Error(TokenError::UnterminatedMultilineComment),
+    );
+}
+
+#[test]
+fn test_multiline_comment_across_lines() {
+    let mut lexer = Lexer::new();
+    let mut frames: Vec<TokenFrame> = Vec::new();
+    for line in ["x /* first", "second", "third */ y"] {
+        lexer.read_line(line);
+        while let Some(frame) = lexer.next_token() {
+            frames.push(frame);
+        }
+    }
+
+    assert_eq!(frames.len(), 3);
+    assert_eq!(
+        frames[1].token,
+        Token::MultilineComment("/* first\nsecond\nthird */".to_string())
+    );
+    assert_position(&frames[1], 2, 1, 3);
+    assert_eq!(frames[1].end, 26);
+    assert_position(&frames[2], 27, 3, 10);
+}
+
+#[test]
+fn test_multiline_comment_waits_for_input() {
+    let mut lexer = Lexer::new();
+    lexer.read_line("/* still open");
+    assert_eq!(lexer.next_token(), None);
+
+    lexer.finish();
+    let frame = lexer.next_token().expect("expected an error token");
+    assert_eq!(
+        frame.token,
+        Token::Error(TokenError::UnterminatedMultilineComment)
+    );
+    assert_position(&frame, 0, 1, 1);
+}
+
+#[test]
+fn test_template_literal() {
+    assert_token("``", Token::TemplateLiteral("".to_string()));
+    assert_token(
+        "`hello world`",
+        Token::TemplateLiteral("hello world".to_string()),
+    );
+    assert_token(
+        // escaped backtick and interpolation
+        r#"`a \` b \${c}`"#,
+        Token::TemplateLiteral(r#"a \` b \${c}"#.to_string()),
+    );
+    assert_token(
+        // lone dollar sign and brace
+        "`$5 {x}`",
+        Token::TemplateLiteral("$5 {x}".to_string()),
+    );
+    assert_tokens(
+        "`hello ${name}!`",
+        vec![
+            Token::TemplateHead("hello ".to_string()),
+            Token::Identifier("name".to_string()),
+            Token::TemplateTail("!".to_string()),
+        ],
+    );
+    assert_tokens(
+        "`${a}${b}`",
+        vec![
+            Token::TemplateHead("".to_string()),
+            Token::Identifier("a".to_string()),
+            Token::TemplateMiddle("".to_string()),
+            Token::Identifier("b".to_string()),
+            Token::TemplateTail("".to_string()),
+        ],
+    );
+    assert_tokens(
+        // braces inside an interpolation
+        "`${ {x} }`",
+        vec![
+            Token::TemplateHead("".to_string()),
+            Token::LBrace,
+            Token::Identifier("x".to_string()),
+            Token::RBrace,
+            Token::TemplateTail("".to_string()),
+        ],
+    );
+    assert_tokens(
+        // nested templates
+        "`a ${ `b ${c} d` } e`",
+        vec![
+            Token::TemplateHead("a ".to_string()),
+            Token::TemplateHead("b ".to_string()),
+            Token::Identifier("c".to_string()),
+            Token::TemplateTail(" d".to_string()),
+            Token::TemplateTail(" e".to_string()),
+        ],
+    );
+    assert_tokens(
+        // braces after the template are plain delimiters again
+        "`${x}` }",
+        vec![
+            Token::TemplateHead("".to_string()),
+            Token::Identifier("x".to_string()),
+            Token::TemplateTail("".to_string()),
+            Token::RBrace,
+        ],
+    );
+    assert_token(
+        "`unterminated",
+        Token::Error(TokenError::UnterminatedTemplateLiteral),
+    );
+    assert_tokens(
+        "`${x} unterminated",
+        vec![
+            Token::TemplateHead("".to_string()),
+            Token::Identifier("x".to_string()),
+            Token::Error(TokenError::UnterminatedTemplateLiteral),
+        ],
+    );
+}
+
+#[test]
+fn test_template_literal_across_lines() {
+    let mut lexer = Lexer::new();
+    let mut frames: Vec<TokenFrame> = Vec::new();
+    for line in ["`first ${", "  x", "} second", "third`;"] {
+        lexer.read_line(line);
+        while let Some(frame) = lexer.next_token() {
+            frames.push(frame);
+        }
+    }
+
+    let tokens: Vec<Token> = frames.iter().map(|frame| frame.token.clone()).collect();
+    assert_eq!(
+        tokens,
+        vec![
+            Token::TemplateHead("first ".to_string()),
+            Token::Identifier("x".to_string()),
+            Token::TemplateTail(" second\nthird".to_string()),
+            Token::Semi,
+        ]
+    );
+    assert_position(&frames[1], 12, 2, 3);
+    assert_position(&frames[2], 14, 3, 1);
+    assert_position(&frames[3], 29, 4, 7);
+}
+
+#[test]
+fn test_unicode_identifiers() {
+    assert_token("café", Token::Identifier("café".to_string()));
+    assert_token("变量", Token::Identifier("变量".to_string()));
+    assert_token("_größe2", Token::Identifier("_größe2".to_string()));
+    assert_token("e\u{0301}", Token::Identifier("\u{00E9}".to_string()));
+    assert_tokens("€", vec![Token::Error(TokenError::Illegal('€'))]);
+}
+
+#[test]
+fn test_unicode_identifiers_are_normalized() {
+    let composed = read_tokens("caf\u{00E9}");
+    let decomposed = read_tokens("cafe\u{0301}");
+    assert_eq!(composed[0].token, decomposed[0].token);
+    // Spans still cover the source as written.
+    assert_eq!(decomposed[0].end, 5);
+}
+
+#[test]
+fn test_unicode_whitespace() {
+    assert_tokens(
+        "a\u{00A0}b\u{3000}c\u{200E}d",
+        vec![
+            Token::Identifier("a".to_string()),
+            Token::Identifier("b".to_string()),
+            Token::Identifier("c".to_string()),
+            Token::Identifier("d".to_string()),
+        ],
+    );
+}
+
+#[test]
+fn test_confusable_warnings() {
+    let mut lexer = Lexer::new();
+    lexer.read_line(
+        "let \u{0440}\u{043E}\u{0441}\u{0442} = p\u{0430}ss + 変数 + \u{0430}\u{0440}\u{0435};",
+    );
+    lexer.finish();
+    while lexer.next_token().is_some() {}
+
+    let warnings = lexer.take_warnings();
+    let warnings: Vec<_> = warnings
+        .iter()
+        .map(|frame| (&frame.warning, frame.column))
+        .collect();
+    assert_eq!(
+        warnings,
+        vec![
+            (
+                &TokenWarning::ConfusableCharacter {
+                    identifier: "p\u{0430}ss".to_string(),
+                    character: '\u{0430}',
+                    looks_like: "a".to_string(),
+                },
+                12
+            ),
+            (
+                &TokenWarning::ConfusableCharacter {
+                    identifier: "\u{0430}\u{0440}\u{0435}".to_string(),
+                    character: '\u{0430}',
+                    looks_like: "a".to_string(),
+                },
+                24
+            ),
+        ]
+    );
+    assert!(lexer.take_warnings().is_empty());
+}
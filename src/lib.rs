@@ -1,7 +1,10 @@
 pub mod ast;
 pub mod lexer;
+pub mod module;
 pub mod parser;
+pub mod syntax;
 pub mod token;
+pub mod validate;
 
 #[cfg(test)]
 mod lexer_tests;
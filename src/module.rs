@@ -0,0 +1,228 @@
+use crate::ast::*;
+use crate::parser::Parser;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const SOURCE_EXTENSION: &str = "foo";
+
+/// A parsed source file and the modules nested below it. The module
+/// `a::b` lives in `a/b.foo` next to the entry file.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Module {
+    pub name: String,
+    /// Missing for a directory that only holds other modules.
+    pub file: Option<PathBuf>,
+    pub program: Program,
+    pub submodules: Vec<Module>,
+}
+
+impl Module {
+    pub fn submodule(&self, name: &str) -> Option<&Module> {
+        self.submodules.iter().find(|module| module.name == name)
+    }
+
+    /// Looks up a module by its path below this one.
+    pub fn find(&self, path: &[&str]) -> Option<&Module> {
+        path.iter()
+            .try_fold(self, |module, name| module.submodule(name))
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub enum ModuleError {
+    /// No module on disk matches the path of a `use` in `file`.
+    UnresolvedImport {
+        file: PathBuf,
+        path: Vec<String>,
+    },
+    Unreadable {
+        file: PathBuf,
+        message: String,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ModuleTree {
+    pub root: Module,
+    pub errors: Vec<ModuleError>,
+}
+
+/// Parses `entry` and every module reachable from it through `use`.
+/// Only failing to read the entry file itself is an error; problems with
+/// the modules it imports are collected in `ModuleTree::errors`.
+pub fn load_modules<P: AsRef<Path>>(entry: P) -> Result<ModuleTree, io::Error> {
+    let entry = entry.as_ref();
+    let program = Parser::parse_file(entry)?;
+
+    let mut loader = Loader {
+        root_dir: entry.parent().unwrap_or(Path::new("")).to_path_buf(),
+        loaded: BTreeMap::new(),
+        pending: VecDeque::new(),
+        errors: Vec::new(),
+    };
+    loader.insert(Vec::new(), Some(entry.to_path_buf()), program);
+
+    while let Some(path) = loader.pending.pop_front() {
+        let (file, imports) = match loader.loaded.get(&path) {
+            Some((Some(file), program)) => (file.clone(), collect_imports(program)),
+            _ => continue,
+        };
+        for import in imports {
+            loader.resolve(&file, import);
+        }
+    }
+
+    let name = entry
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let root = build_module(name, Vec::new(), &mut loader.loaded);
+
+    Ok(ModuleTree {
+        root,
+        errors: loader.errors,
+    })
+}
+
+struct Import {
+    path: Vec<String>,
+    is_glob: bool,
+}
+
+struct Loader {
+    root_dir: PathBuf,
+    loaded: BTreeMap<Vec<String>, (Option<PathBuf>, Program)>,
+    // Modules whose imports have not been followed yet.
+    pending: VecDeque<Vec<String>>,
+    errors: Vec<ModuleError>,
+}
+
+impl Loader {
+    fn insert(&mut self, path: Vec<String>, file: Option<PathBuf>, program: Program) {
+        if file.is_some() {
+            self.pending.push_back(path.clone());
+        }
+        self.loaded.insert(path, (file, program));
+    }
+
+    /// Finds the module an import refers to. In `a::b::c` the module may be
+    /// `a::b::c` itself or, with `c` an item inside it, `a::b`, and so on;
+    /// the longest path that exists on disk wins. A glob always names a
+    /// module.
+    fn resolve(&mut self, file: &Path, import: Import) {
+        let shortest = if import.is_glob { import.path.len() } else { 1 };
+        for len in (shortest..=import.path.len()).rev() {
+            if self.load(&import.path[..len]) {
+                return;
+            }
+        }
+        self.errors.push(ModuleError::UnresolvedImport {
+            file: file.to_path_buf(),
+            path: import.path,
+        });
+    }
+
+    /// Loads the module at `path`, and the modules enclosing it, unless they
+    /// are loaded already. Returns false if there is no such module.
+    fn load(&mut self, path: &[String]) -> bool {
+        if path.is_empty() {
+            return false;
+        }
+        if self.loaded.contains_key(path) {
+            return true;
+        }
+
+        let dir = path
+            .iter()
+            .fold(self.root_dir.clone(), |dir, name| dir.join(name));
+        let file = dir.with_extension(SOURCE_EXTENSION);
+        if file.is_file() {
+            match Parser::parse_file(&file) {
+                Ok(program) => self.insert(path.to_vec(), Some(file), program),
+                Err(err) => {
+                    self.errors.push(ModuleError::Unreadable {
+                        file: file.clone(),
+                        message: err.to_string(),
+                    });
+                    self.insert(path.to_vec(), Some(file), Program::new());
+                }
+            }
+        } else if dir.is_dir() {
+            self.insert(path.to_vec(), None, Program::new());
+        } else {
+            return false;
+        }
+
+        self.load(&path[..path.len() - 1]);
+        true
+    }
+}
+
+fn collect_imports(program: &Program) -> Vec<Import> {
+    let mut imports: Vec<Import> = Vec::new();
+    for statement in &program.statements {
+        if let Node::Use(node) = statement {
+            collect_use_tree(&node.tree, &[], &mut imports);
+        }
+    }
+    imports
+}
+
+fn collect_use_tree(tree: &Node, prefix: &[String], imports: &mut Vec<Import>) {
+    let joined = |path: &[Node]| {
+        let mut joined = prefix.to_vec();
+        joined.extend(path.iter().filter_map(|segment| match segment {
+            Node::Ident(ident) => Some(ident.identifier.clone()),
+            _ => None,
+        }));
+        joined
+    };
+
+    match tree {
+        Node::UsePath(node) => imports.push(Import {
+            path: joined(&node.path),
+            is_glob: false,
+        }),
+        Node::UseGlob(node) => imports.push(Import {
+            path: joined(&node.path),
+            is_glob: true,
+        }),
+        Node::UseGroup(node) => {
+            let prefix = joined(&node.path);
+            for item in &node.items {
+                collect_use_tree(item, &prefix, imports);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn build_module(
+    name: String,
+    path: Vec<String>,
+    loaded: &mut BTreeMap<Vec<String>, (Option<PathBuf>, Program)>,
+) -> Module {
+    let (file, program) = loaded.remove(&path).unwrap_or((None, Program::new()));
+
+    let children: Vec<Vec<String>> = loaded
+        .keys()
+        .filter(|child| child.len() == path.len() + 1 && child.starts_with(&path))
+        .cloned()
+        .collect();
+    let submodules = children
+        .into_iter()
+        .map(|child| {
+            let name = child[path.len()].clone();
+            build_module(name, child, loaded)
+        })
+        .collect();
+
+    Module {
+        name,
+        file,
+        program,
+        submodules,
+    }
+}
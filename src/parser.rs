@@ -1,81 +1,203 @@
 use crate::ast::*;
 use crate::lexer::*;
 use crate::token::*;
-use std::fs::File;
+use crate::validate::validate;
+use std::collections::HashMap;
+use std::fs;
 use std::io;
-use std::io::{BufRead, BufReader};
+use std::path::Path;
 use std::vec::IntoIter;
 
+/// Number of errors after which parsing gives up, unless overridden with
+/// `Parser::with_max_errors`.
+pub const DEFAULT_MAX_ERRORS: usize = 100;
+
 pub struct Parser {
+    errors: Vec<ParseError>,
+    max_errors: usize,
+    // Labels of the loops enclosing the current statement, innermost last.
+    loops: Vec<Option<String>>,
+    // Off in the head of `if`, `while`, `for` and `match`, where a `{`
+    // after a name opens the body instead of a struct literal.
+    is_struct_allowed: bool,
+    // Off in a match guard, where `(a) =>` ends the guard rather than
+    // opening a closure. Back on inside call arguments and array elements.
+    is_arrow_closure_allowed: bool,
+    previous_end: usize,
+    // Doc comment text, keyed by the start of the token that follows it.
+    docs: HashMap<usize, String>,
     current: TokenFrame,
     peek: Option<TokenFrame>,
     iter: IntoIter<TokenFrame>,
 }
 
 impl Parser {
-    pub fn parse_file(file_path: &str) -> Result<Program, io::Error> {
+    pub fn parse_file<P: AsRef<Path>>(file_path: P) -> Result<Program, io::Error> {
+        let source = fs::read_to_string(file_path)?;
         let mut lexer = Lexer::new();
         let mut tokens: Vec<TokenFrame> = Vec::new();
 
-        let file = File::open(file_path)?;
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
-            lexer.read_line(&line?);
-            while let Some(frame) = lexer.next_token() {
-                tokens.push(frame);
-            }
+        lexer.read_source(&source);
+        lexer.finish();
+        while let Some(frame) = lexer.next_token() {
+            tokens.push(frame);
         }
 
-        let mut parser = Parser::new(tokens);
-        Ok(create_program(&mut parser))
+        let mut program = Parser::new(tokens).parse_program();
+        program.warnings = lexer.take_warnings();
+        Ok(program)
     }
 
     pub fn parse_source(lines: Vec<&str>) -> Program {
         let mut lexer = Lexer::new();
-        let mut lines_iter = lines.into_iter();
         let mut tokens: Vec<TokenFrame> = Vec::new();
 
-        while let Some(line) = lines_iter.next() {
+        for line in lines {
             lexer.read_line(line);
             while let Some(frame) = lexer.next_token() {
                 tokens.push(frame);
             }
         }
 
-        let mut parser = Parser::new(tokens);
-        create_program(&mut parser)
+        lexer.finish();
+        while let Some(frame) = lexer.next_token() {
+            tokens.push(frame);
+        }
+
+        let mut program = Parser::new(tokens).parse_program();
+        program.warnings = lexer.take_warnings();
+        program
     }
 
     pub fn new(tokens: Vec<TokenFrame>) -> Self {
+        // Comments carry no meaning for the grammar, so they never reach it.
+        // Doc comments are set aside for the declaration that follows them.
+        let mut docs: HashMap<usize, String> = HashMap::new();
+        let mut doc_lines: Vec<String> = Vec::new();
+        let mut grammar_tokens: Vec<TokenFrame> = Vec::new();
+        for frame in tokens {
+            match &frame.token {
+                Token::DocComment(comment) => doc_lines.push(doc_text(comment)),
+                token if token.is_comment() => {}
+                _ => {
+                    if !doc_lines.is_empty() {
+                        docs.insert(frame.start, std::mem::take(&mut doc_lines).join("\n"));
+                    }
+                    grammar_tokens.push(frame);
+                }
+            }
+        }
+        let mut iter = grammar_tokens.into_iter();
+        let peek = iter.next();
         Self {
-            iter: tokens.into_iter(),
+            iter,
+            errors: Vec::new(),
+            max_errors: DEFAULT_MAX_ERRORS,
+            loops: Vec::new(),
+            is_struct_allowed: true,
+            is_arrow_closure_allowed: true,
+            previous_end: 0,
+            docs,
             current: TokenFrame::empty(),
-            peek: None,
+            peek,
+        }
+    }
+
+    /// Caps the number of errors collected before parsing stops. A final
+    /// `ParseError::TooManyErrors` is recorded when the cap is reached.
+    pub fn with_max_errors(mut self, max_errors: usize) -> Self {
+        self.max_errors = max_errors;
+        self
+    }
+
+    pub fn parse_program(mut self) -> Program {
+        create_program(&mut self)
+    }
+
+    fn report_error(&mut self, error: ParseError) {
+        if self.has_given_up() {
+            return;
+        }
+        self.errors.push(error);
+        if self.errors.len() >= self.max_errors {
+            self.errors.push(ParseError::TooManyErrors);
         }
     }
 
+    /// Runs `parse` with struct literals allowed or not, then restores the
+    /// previous setting.
+    fn with_struct_literals<T>(
+        &mut self,
+        is_allowed: bool,
+        parse: impl FnOnce(&mut Parser) -> T,
+    ) -> T {
+        let enclosing = std::mem::replace(&mut self.is_struct_allowed, is_allowed);
+        let result = parse(self);
+        self.is_struct_allowed = enclosing;
+        result
+    }
+
+    /// Runs `parse` with arrow closures allowed or not, then restores the
+    /// previous setting.
+    fn with_arrow_closures<T>(
+        &mut self,
+        is_allowed: bool,
+        parse: impl FnOnce(&mut Parser) -> T,
+    ) -> T {
+        let enclosing = std::mem::replace(&mut self.is_arrow_closure_allowed, is_allowed);
+        let result = parse(self);
+        self.is_arrow_closure_allowed = enclosing;
+        result
+    }
+
+    fn has_given_up(&self) -> bool {
+        self.errors.last() == Some(&ParseError::TooManyErrors)
+    }
+
     fn span(&self) -> Span {
         Span {
             start: self.current.start,
             end: self.current.end,
+            line: self.current.line,
+            column: self.current.column,
+        }
+    }
+
+    /// Extends `start` to the end of the most recently consumed token.
+    fn span_from(&self, start: Span) -> Span {
+        Span {
+            end: self.previous_end.max(start.end),
+            ..start
         }
     }
 
     fn advance_token(&mut self) -> bool {
-        let current_option = self.iter.next();
-        if let Some(current) = current_option {
-            self.current = self.peek.take().unwrap_or_else(TokenFrame::empty);
-            self.peek = Some(current);
-            return true;
-        } else if self.peek.is_some() {
-            self.current = self.peek.take().unwrap();
-            return true;
+        if self.current.token != Token::EOF {
+            self.previous_end = self.current.end;
+        }
+        match self.peek.take() {
+            Some(next) => {
+                self.current = next;
+                self.peek = self.iter.next();
+                true
+            }
+            None => {
+                // Past the last token the parser sits on an EOF frame placed
+                // directly after it, so errors at end of input still point
+                // somewhere sensible.
+                let last = &self.current;
+                self.current = TokenFrame {
+                    token: Token::EOF,
+                    start: last.end,
+                    end: last.end,
+                    line: last.line,
+                    column: last.column + (last.end - last.start),
+                };
+                false
+            }
         }
-        false
     }
 
-    #[allow(dead_code)]
     fn peek_token(&self) -> &Token {
         match &self.peek {
             Some(peek) => &peek.token,
@@ -92,11 +214,11 @@ impl Parser {
     }
 
     fn expect_token(&self, token: Token) -> Result<(), ParseError> {
-        return if self.current.token == token {
+        if self.current.token == token {
             Ok(())
         } else {
             Err(ParseError::UnexpectedToken(self.current.clone()))
-        };
+        }
     }
 
     fn expect_keyword(&self, keywords: Vec<Keyword>) -> Result<(), ParseError> {
@@ -135,115 +257,2324 @@ fn unexpected_token(p: &mut Parser) -> ParseError {
     ParseError::UnexpectedToken(p.current_frame().clone())
 }
 
-fn synchronize(_p: &mut Parser, _program: &Program) {
-    // todo: skip tokens until a recovery point is found.
+fn starts_statement(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Keyword(
+            Keyword::ASYNC
+                | Keyword::BREAK
+                | Keyword::CONST
+                | Keyword::CONTINUE
+                | Keyword::FOR
+                | Keyword::FUNC
+                | Keyword::IF
+                | Keyword::IMPL
+                | Keyword::LET
+                | Keyword::MATCH
+                | Keyword::PUB
+                | Keyword::RETURN
+                | Keyword::TRAIT
+                | Keyword::TYPE
+                | Keyword::USE
+                | Keyword::WHILE
+        )
+    )
+}
+
+/// Skips tokens after an error until a point where parsing can resume: just
+/// past a `;`, or before a `}` or a keyword that starts a statement.
+/// `statement_start` is the offset of the statement that failed; if the
+/// error was raised on its very first token that token is skipped, so
+/// recovery always makes progress.
+fn synchronize(p: &mut Parser, statement_start: usize) {
+    if p.current_frame().start == statement_start {
+        p.advance_token();
+    }
+
+    loop {
+        match p.current_token() {
+            Token::EOF | Token::RBrace => return,
+            Token::Semi => {
+                p.advance_token();
+                return;
+            }
+            token if starts_statement(token) => return,
+            _ => {
+                p.advance_token();
+            }
+        }
+    }
 }
 
 fn create_program(p: &mut Parser) -> Program {
     let mut program = Program::new();
 
-    p.advance_token(); // prime the peek token
+    p.advance_token(); // load the first token
 
-    while p.advance_token() {
+    while *p.current_token() != Token::EOF && !p.has_given_up() {
+        let statement_start = p.current_frame().start;
         match parse_root_statement(p) {
             Ok(statement) => program.statements.push(statement),
             Err(err) => {
-                program.errors.push(err);
-                synchronize(p, &program);
-                return program;
+                p.report_error(err);
+                synchronize(p, statement_start);
             }
         }
     }
 
+    for error in validate(&program) {
+        p.report_error(error);
+    }
+
+    program.errors = std::mem::take(&mut p.errors);
     program
 }
 
 fn parse_root_statement(p: &mut Parser) -> Result<Node, ParseError> {
     let statement = match p.current_token() {
-        Token::Keyword(Keyword::LET) => parse_variable_statement(p),
-        Token::Keyword(Keyword::CONST) => parse_variable_statement(p),
+        Token::Keyword(
+            Keyword::PUB
+            | Keyword::ASYNC
+            | Keyword::LET
+            | Keyword::CONST
+            | Keyword::USE
+            | Keyword::FUNC
+            | Keyword::TYPE
+            | Keyword::TRAIT
+            | Keyword::IMPL,
+        ) => parse_declaration(p, DeclarationContext::Statement),
         _ => Err(unexpected_token(p)),
     }?;
     Ok(statement)
 }
 
-fn parse_variable_statement(p: &mut Parser) -> Result<Node, ParseError> {
+/// Statements that may appear inside a block.
+fn parse_statement(p: &mut Parser) -> Result<Node, ParseError> {
+    match p.current_token() {
+        Token::Keyword(Keyword::ASYNC) if *p.peek_token() == Token::LBrace => {
+            parse_expression_statement(p)
+        }
+        Token::Keyword(
+            Keyword::PUB
+            | Keyword::ASYNC
+            | Keyword::LET
+            | Keyword::CONST
+            | Keyword::USE
+            | Keyword::FUNC
+            | Keyword::TYPE
+            | Keyword::TRAIT
+            | Keyword::IMPL,
+        ) => parse_declaration(p, DeclarationContext::Statement),
+        Token::Keyword(Keyword::IF) => parse_if_statement(p),
+        Token::Keyword(Keyword::RETURN) => parse_return_statement(p),
+        Token::Keyword(Keyword::WHILE | Keyword::FOR) => parse_loop_statement(p),
+        Token::Identifier(_) if *p.peek_token() == Token::Colon => parse_loop_statement(p),
+        Token::Keyword(Keyword::MATCH) => parse_match_statement(p),
+        Token::Keyword(Keyword::BREAK) => parse_break_statement(p),
+        Token::Keyword(Keyword::CONTINUE) => parse_continue_statement(p),
+        Token::LBrace => parse_block(p),
+        _ => parse_expression_statement(p),
+    }
+}
+
+fn parse_if_statement(p: &mut Parser) -> Result<Node, ParseError> {
     let location = p.span();
+    p.expect_keyword(vec![Keyword::IF])?;
+    p.advance_token();
 
-    p.expect_keyword(vec![Keyword::LET, Keyword::CONST])?;
-    let keyword = parse_keyword(p)?;
+    let condition = p.with_struct_literals(false, parse_expression)?;
 
-    let identifier = parse_identifier(p)?;
+    let then_block = parse_block(p)?;
 
-    p.expect_token(Token::Equal)?;
+    let else_branch = match p.current_token() {
+        Token::Keyword(Keyword::ELSE) => {
+            p.advance_token();
+            let branch = match p.current_token() {
+                Token::Keyword(Keyword::IF) => parse_if_statement(p),
+                _ => parse_block(p),
+            }?;
+            Some(Box::new(branch))
+        }
+        _ => None,
+    };
+
+    Ok(Node::If(IfNode {
+        span: p.span_from(location),
+        condition: Box::new(condition),
+        then_block: Box::new(then_block),
+        else_branch,
+    }))
+}
+
+/// Parses a `while` or `for` loop, with an optional `label:` in front.
+fn parse_loop_statement(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+
+    let label = match p.current_token() {
+        Token::Identifier(_) => {
+            let label = parse_identifier(p)?;
+            p.expect_token(Token::Colon)?;
+            p.advance_token();
+            Some(label)
+        }
+        _ => None,
+    };
+    let label_name = match &label {
+        Some(Node::Ident(ident)) => Some(ident.identifier.clone()),
+        _ => None,
+    };
+
+    p.expect_keyword(vec![Keyword::WHILE, Keyword::FOR])?;
+    let is_while = *p.current_token() == Token::Keyword(Keyword::WHILE);
+    p.advance_token();
+
+    if is_while {
+        let condition = p.with_struct_literals(false, parse_expression)?;
+        let body = parse_loop_body(p, label_name)?;
+        return Ok(Node::While(WhileNode {
+            span: p.span_from(location),
+            label: label.map(Box::new),
+            condition: Box::new(condition),
+            body: Box::new(body),
+        }));
+    }
+
+    let binding = parse_identifier(p)?;
+
+    p.expect_keyword(vec![Keyword::IN])?;
+    p.advance_token();
+
+    let iterable = p.with_struct_literals(false, parse_expression)?;
+    let body = parse_loop_body(p, label_name)?;
+
+    Ok(Node::For(ForNode {
+        span: p.span_from(location),
+        label: label.map(Box::new),
+        binding: Box::new(binding),
+        iterable: Box::new(iterable),
+        body: Box::new(body),
+    }))
+}
+
+fn parse_loop_body(p: &mut Parser, label: Option<String>) -> Result<Node, ParseError> {
+    p.loops.push(label);
+    let body = parse_block(p);
+    p.loops.pop();
+    body
+}
+
+fn is_loop_label(p: &Parser, name: &str) -> bool {
+    p.loops.iter().flatten().any(|label| label == name)
+}
+
+fn parse_break_statement(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    p.expect_keyword(vec![Keyword::BREAK])?;
     p.advance_token();
 
-    let literal = parse_literal(p)?;
+    if p.loops.is_empty() {
+        p.report_error(ParseError::BreakOutsideLoop(location));
+    }
+
+    // An identifier that does not name an enclosing loop is the value.
+    let label = match p.current_token() {
+        Token::Identifier(name) if is_loop_label(p, name) => Some(Box::new(parse_identifier(p)?)),
+        _ => None,
+    };
+
+    let value = match p.current_token() {
+        Token::Semi => None,
+        _ => Some(Box::new(parse_expression(p)?)),
+    };
 
     let semi = match p.current_token() {
         Token::Semi => parse_semi(p),
         _ => Err(unexpected_token(p)),
     }?;
 
-    Ok(Node::Variable(VariableNode {
-        span: location,
-        keyword: Box::new(keyword),
-        identifier: Box::new(identifier),
-        literal: Box::new(literal),
+    Ok(Node::Break(BreakNode {
+        span: p.span_from(location),
+        label,
+        value,
         semi: Box::new(semi),
     }))
 }
 
-fn parse_keyword(p: &mut Parser) -> Result<Node, ParseError> {
+fn parse_continue_statement(p: &mut Parser) -> Result<Node, ParseError> {
     let location = p.span();
-    let keyword = match p.current_token() {
-        Token::Keyword(keyword) => Ok(keyword.clone()),
+    p.expect_keyword(vec![Keyword::CONTINUE])?;
+    p.advance_token();
+
+    if p.loops.is_empty() {
+        p.report_error(ParseError::ContinueOutsideLoop(location));
+    }
+
+    let label = match p.current_token() {
+        Token::Identifier(_) => {
+            let label = parse_identifier(p)?;
+            if let Node::Ident(ident) = &label {
+                if !is_loop_label(p, &ident.identifier) {
+                    p.report_error(ParseError::UndeclaredLabel(ident.clone()));
+                }
+            }
+            Some(Box::new(label))
+        }
+        _ => None,
+    };
+
+    let semi = match p.current_token() {
+        Token::Semi => parse_semi(p),
         _ => Err(unexpected_token(p)),
     }?;
-    p.advance_token();
-    Ok(Node::Keyword(KeywordNode {
-        span: location,
-        keyword,
+
+    Ok(Node::Continue(ContinueNode {
+        span: p.span_from(location),
+        label,
+        semi: Box::new(semi),
     }))
 }
 
-fn parse_identifier(p: &mut Parser) -> Result<Node, ParseError> {
+fn parse_return_statement(p: &mut Parser) -> Result<Node, ParseError> {
     let location = p.span();
-    let identifier = match p.current_token() {
-        Token::Identifier(identifier) => Ok(identifier.clone()),
+    p.expect_keyword(vec![Keyword::RETURN])?;
+    p.advance_token();
+
+    let value = match p.current_token() {
+        Token::Semi => None,
+        _ => Some(Box::new(parse_expression(p)?)),
+    };
+
+    let semi = match p.current_token() {
+        Token::Semi => parse_semi(p),
         _ => Err(unexpected_token(p)),
     }?;
-    p.advance_token();
-    Ok(Node::Ident(IdentNode {
-        span: location,
-        identifier,
+
+    Ok(Node::Return(ReturnNode {
+        span: p.span_from(location),
+        value,
+        semi: Box::new(semi),
     }))
 }
 
-fn parse_literal(p: &mut Parser) -> Result<Node, ParseError> {
-    return match p.current_token() {
-        Token::NumberLiteral(_) => parse_number_literal(p),
-        _ => Err(unexpected_token(p)),
+/// A `match` used as a statement does not need a trailing `;`.
+fn parse_match_statement(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+
+    let expression = parse_expression(p)?;
+
+    if *p.current_token() != Token::Semi {
+        return Ok(expression);
+    }
+    let semi = parse_semi(p)?;
+
+    Ok(Node::Expression(ExpressionNode {
+        span: p.span_from(location),
+        expression: Box::new(expression),
+        semi: Some(Box::new(semi)),
+    }))
+}
+
+/// Parses an expression followed by `;`. The last expression in a block
+/// may leave it out to give the block its value.
+fn parse_expression_statement(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+
+    let expression = parse_expression(p)?;
+
+    let semi = match p.current_token() {
+        Token::Semi => Some(Box::new(parse_semi(p)?)),
+        Token::RBrace => None,
+        _ => return Err(unexpected_token(p)),
     };
+
+    Ok(Node::Expression(ExpressionNode {
+        span: p.span_from(location),
+        expression: Box::new(expression),
+        semi,
+    }))
 }
 
-fn parse_number_literal(p: &mut Parser) -> Result<Node, ParseError> {
+/// Where a declaration appears, which decides the forms it may take.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum DeclarationContext {
+    Statement,
+    Trait,
+    Impl,
+}
+
+/// Parses the modifiers in front of a declaration and the declaration that
+/// follows them.
+fn parse_declaration(p: &mut Parser, context: DeclarationContext) -> Result<Node, ParseError> {
     let location = p.span();
-    let number = match p.current_token() {
-        Token::NumberLiteral(literal) => Ok(literal.clone()),
+
+    let mut visibility = Visibility::Private;
+    if *p.current_token() == Token::Keyword(Keyword::PUB) {
+        visibility = Visibility::Public;
+        p.advance_token();
+    }
+
+    let mut is_async = false;
+    if *p.current_token() == Token::Keyword(Keyword::ASYNC) {
+        is_async = true;
+        p.advance_token();
+    }
+
+    let docs = p.docs.remove(&location.start);
+    let is_plain = visibility == Visibility::Private && !is_async;
+    let mut declaration = match (p.current_token(), context) {
+        (Token::Keyword(Keyword::LET | Keyword::CONST), DeclarationContext::Statement)
+            if !is_async =>
+        {
+            parse_variable_statement(p, location, visibility)
+        }
+        (Token::Keyword(Keyword::USE), DeclarationContext::Statement) if !is_async => {
+            parse_use(p, location, visibility)
+        }
+        (Token::Keyword(Keyword::FUNC), _) => {
+            let is_signature_allowed = context == DeclarationContext::Trait;
+            parse_function(p, location, visibility, is_async, is_signature_allowed)
+        }
+        (Token::Keyword(Keyword::TYPE), DeclarationContext::Trait) if is_plain => {
+            parse_associated_type(p, location)
+        }
+        (Token::Keyword(Keyword::TYPE), _) if !is_async => {
+            parse_type_declaration(p, location, visibility)
+        }
+        (Token::Keyword(Keyword::TRAIT), DeclarationContext::Statement) if !is_async => {
+            parse_trait(p, location, visibility)
+        }
+        (Token::Keyword(Keyword::IMPL), DeclarationContext::Statement) if is_plain => {
+            parse_impl(p, location)
+        }
+        (Token::Keyword(kw), _) => Err(ParseError::UnexpectedKeyword(kw.clone())),
         _ => Err(unexpected_token(p)),
     }?;
+
+    if let Some(slot) = declaration_docs(&mut declaration) {
+        *slot = docs;
+    }
+    Ok(declaration)
+}
+
+fn declaration_docs(declaration: &mut Node) -> Option<&mut Option<String>> {
+    match declaration {
+        Node::Variable(node) => Some(&mut node.docs),
+        Node::Function(node) => Some(&mut node.docs),
+        Node::TypeAlias(node) => Some(&mut node.docs),
+        Node::Record(node) => Some(&mut node.docs),
+        Node::Trait(node) => Some(&mut node.docs),
+        Node::AssociatedType(node) => Some(&mut node.docs),
+        _ => None,
+    }
+}
+
+/// The text of a doc comment without its delimiters. One space after `///`
+/// is dropped, and in a block comment so is the `*` that may lead each line
+/// along with the blank lines around the text.
+fn doc_text(comment: &str) -> String {
+    if let Some(line) = comment.strip_prefix("///") {
+        return line.strip_prefix(' ').unwrap_or(line).to_string();
+    }
+
+    let body = comment
+        .strip_prefix("/**")
+        .and_then(|body| body.strip_suffix("*/"))
+        .unwrap_or(comment);
+    let lines: Vec<&str> = body
+        .lines()
+        .map(|line| {
+            let line = line.trim_start();
+            let line = line.strip_prefix('*').unwrap_or(line);
+            line.strip_prefix(' ').unwrap_or(line).trim_end()
+        })
+        .collect();
+    let first = lines.iter().position(|line| !line.is_empty());
+    let last = lines.iter().rposition(|line| !line.is_empty());
+    match (first, last) {
+        (Some(first), Some(last)) => lines[first..=last].join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn parse_use(p: &mut Parser, location: Span, visibility: Visibility) -> Result<Node, ParseError> {
+    p.expect_keyword(vec![Keyword::USE])?;
     p.advance_token();
-    Ok(Node::Number(NumberNode {
-        span: location,
-        kind: number.kind,
-        postfix: number.postfix,
-        value: number.value,
+
+    let tree = parse_use_tree(p)?;
+
+    p.expect_token(Token::Semi)?;
+    let semi = parse_semi(p)?;
+
+    Ok(Node::Use(UseNode {
+        span: p.span_from(location),
+        visibility,
+        tree: Box::new(tree),
+        semi: Box::new(semi),
     }))
 }
 
-fn parse_semi(p: &mut Parser) -> Result<Node, ParseError> {
-    p.advance_token();
+/// Parses what follows `use`: `a::b`, `a::b as c`, `a::*` or `a::{...}`.
+fn parse_use_tree(p: &mut Parser) -> Result<Node, ParseError> {
     let location = p.span();
+
+    let mut path: Vec<Node> = Vec::new();
+    loop {
+        match p.current_token() {
+            Token::Asterisk if !path.is_empty() => {
+                p.advance_token();
+                return Ok(Node::UseGlob(UseGlobNode {
+                    span: p.span_from(location),
+                    path,
+                }));
+            }
+            Token::LBrace => {
+                let items = parse_use_group(p)?;
+                return Ok(Node::UseGroup(UseGroupNode {
+                    span: p.span_from(location),
+                    path,
+                    items,
+                }));
+            }
+            _ => path.push(parse_identifier(p)?),
+        }
+
+        if *p.current_token() != Token::DblColon {
+            break;
+        }
+        p.advance_token();
+    }
+
+    let alias = match p.current_token() {
+        Token::Keyword(Keyword::AS) => {
+            p.advance_token();
+            Some(Box::new(parse_identifier(p)?))
+        }
+        _ => None,
+    };
+
+    Ok(Node::UsePath(UsePathNode {
+        span: p.span_from(location),
+        path,
+        alias,
+    }))
+}
+
+fn parse_use_group(p: &mut Parser) -> Result<Vec<Node>, ParseError> {
+    p.expect_token(Token::LBrace)?;
+    p.advance_token();
+
+    let mut items: Vec<Node> = Vec::new();
+    while *p.current_token() != Token::RBrace {
+        items.push(parse_use_tree(p)?);
+        if *p.current_token() == Token::Comma {
+            p.advance_token();
+        } else {
+            break;
+        }
+    }
+
+    p.expect_token(Token::RBrace)?;
+    p.advance_token();
+    Ok(items)
+}
+
+fn parse_trait(p: &mut Parser, location: Span, visibility: Visibility) -> Result<Node, ParseError> {
+    p.expect_keyword(vec![Keyword::TRAIT])?;
+    p.advance_token();
+
+    let identifier = parse_identifier(p)?;
+
+    let generics = match p.current_token() {
+        Token::LessThan => parse_generic_params(p)?,
+        _ => Vec::new(),
+    };
+
+    let where_clause = parse_where_clause(p)?;
+
+    let items = parse_item_block(p, DeclarationContext::Trait)?;
+
+    Ok(Node::Trait(TraitNode {
+        span: p.span_from(location),
+        docs: None,
+        visibility,
+        identifier: Box::new(identifier),
+        generics,
+        where_clause,
+        items,
+    }))
+}
+
+fn parse_impl(p: &mut Parser, location: Span) -> Result<Node, ParseError> {
+    p.expect_keyword(vec![Keyword::IMPL])?;
+    p.advance_token();
+
+    let generics = match p.current_token() {
+        Token::LessThan => parse_generic_params(p)?,
+        _ => Vec::new(),
+    };
+
+    let first_type = parse_type(p)?;
+
+    let (trait_type, self_type) = match p.current_token() {
+        Token::Keyword(Keyword::FOR) => {
+            p.advance_token();
+            (Some(Box::new(first_type)), parse_type(p)?)
+        }
+        _ => (None, first_type),
+    };
+
+    let where_clause = parse_where_clause(p)?;
+
+    let items = parse_item_block(p, DeclarationContext::Impl)?;
+
+    Ok(Node::Impl(ImplNode {
+        span: p.span_from(location),
+        generics,
+        trait_type,
+        self_type: Box::new(self_type),
+        where_clause,
+        items,
+    }))
+}
+
+/// Parses the declarations between the braces of a trait or impl,
+/// recovering from errors in the same way as a block.
+fn parse_item_block(p: &mut Parser, context: DeclarationContext) -> Result<Vec<Node>, ParseError> {
+    p.expect_token(Token::LBrace)?;
+    p.advance_token();
+
+    let mut items: Vec<Node> = Vec::new();
+    while !matches!(p.current_token(), Token::RBrace | Token::EOF) && !p.has_given_up() {
+        let item_start = p.current_frame().start;
+        match parse_declaration(p, context) {
+            Ok(item) => items.push(item),
+            Err(err) => {
+                p.report_error(err);
+                synchronize(p, item_start);
+            }
+        }
+    }
+
+    p.expect_token(Token::RBrace)?;
+    p.advance_token();
+    Ok(items)
+}
+
+fn parse_associated_type(p: &mut Parser, location: Span) -> Result<Node, ParseError> {
+    p.expect_keyword(vec![Keyword::TYPE])?;
+    p.advance_token();
+
+    let identifier = parse_identifier(p)?;
+
+    let bounds = match p.current_token() {
+        Token::Colon => {
+            p.advance_token();
+            parse_bounds(p)?
+        }
+        _ => Vec::new(),
+    };
+
+    p.expect_token(Token::Semi)?;
+    let semi = parse_semi(p)?;
+
+    Ok(Node::AssociatedType(AssociatedTypeNode {
+        span: p.span_from(location),
+        docs: None,
+        identifier: Box::new(identifier),
+        bounds,
+        semi: Box::new(semi),
+    }))
+}
+
+/// Parses `where A: Bound, B: One + Two` if present.
+fn parse_where_clause(p: &mut Parser) -> Result<Vec<Node>, ParseError> {
+    let mut predicates: Vec<Node> = Vec::new();
+    if *p.current_token() != Token::Keyword(Keyword::WHERE) {
+        return Ok(predicates);
+    }
+    p.advance_token();
+
+    loop {
+        let location = p.span();
+        let bounded = parse_type(p)?;
+
+        p.expect_token(Token::Colon)?;
+        p.advance_token();
+
+        let bounds = parse_bounds(p)?;
+
+        predicates.push(Node::WherePredicate(WherePredicateNode {
+            span: p.span_from(location),
+            bounded: Box::new(bounded),
+            bounds,
+        }));
+
+        if *p.current_token() != Token::Comma {
+            break;
+        }
+        p.advance_token();
+        if *p.current_token() == Token::LBrace {
+            break;
+        }
+    }
+
+    Ok(predicates)
+}
+
+/// Parses `One + Two + Three`.
+fn parse_bounds(p: &mut Parser) -> Result<Vec<Node>, ParseError> {
+    let mut bounds = vec![parse_type(p)?];
+    while *p.current_token() == Token::Plus {
+        p.advance_token();
+        bounds.push(parse_type(p)?);
+    }
+    Ok(bounds)
+}
+
+fn parse_function(
+    p: &mut Parser,
+    location: Span,
+    visibility: Visibility,
+    is_async: bool,
+    is_signature_allowed: bool,
+) -> Result<Node, ParseError> {
+    p.expect_keyword(vec![Keyword::FUNC])?;
+    p.advance_token();
+
+    let identifier = parse_identifier(p)?;
+
+    let generics = match p.current_token() {
+        Token::LessThan => parse_generic_params(p)?,
+        _ => Vec::new(),
+    };
+
+    p.expect_token(Token::LParen)?;
+    p.advance_token();
+    let mut parameters: Vec<Node> = Vec::new();
+    if is_self_param(p) {
+        parameters.push(parse_self_param(p)?);
+        if *p.current_token() == Token::Comma {
+            p.advance_token();
+        }
+    }
+    while *p.current_token() != Token::RParen {
+        parameters.push(parse_parameter(p)?);
+        if *p.current_token() == Token::Comma {
+            p.advance_token();
+        } else {
+            break;
+        }
+    }
+    p.expect_token(Token::RParen)?;
+    p.advance_token();
+
+    let return_type = match p.current_token() {
+        Token::Colon => {
+            p.advance_token();
+            Some(Box::new(parse_type(p)?))
+        }
+        _ => None,
+    };
+
+    if is_signature_allowed && *p.current_token() == Token::Semi {
+        p.advance_token();
+        return Ok(Node::Function(FunctionNode {
+            span: p.span_from(location),
+            docs: None,
+            visibility,
+            is_async,
+            identifier: Box::new(identifier),
+            generics,
+            parameters,
+            return_type,
+            body: None,
+        }));
+    }
+
+    // A function body starts a new loop context: `break` inside it can
+    // never refer to a loop around the declaration.
+    let enclosing_loops = std::mem::take(&mut p.loops);
+    let body = parse_block(p);
+    p.loops = enclosing_loops;
+    let body = body?;
+
+    Ok(Node::Function(FunctionNode {
+        span: p.span_from(location),
+        docs: None,
+        visibility,
+        is_async,
+        identifier: Box::new(identifier),
+        generics,
+        parameters,
+        return_type,
+        body: Some(Box::new(body)),
+    }))
+}
+
+fn is_self_param(p: &Parser) -> bool {
+    match p.current_token() {
+        Token::Keyword(Keyword::SELF) => true,
+        Token::Amp => *p.peek_token() == Token::Keyword(Keyword::SELF),
+        _ => false,
+    }
+}
+
+/// Parses the `self` or `&self` receiver of a method.
+fn parse_self_param(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+
+    let is_reference = *p.current_token() == Token::Amp;
+    if is_reference {
+        p.advance_token();
+    }
+
+    p.expect_keyword(vec![Keyword::SELF])?;
+    p.advance_token();
+
+    Ok(Node::SelfParam(SelfParamNode {
+        span: p.span_from(location),
+        is_reference,
+    }))
+}
+
+/// Parses `type Name<T> = ...;`, which declares a record when the right
+/// hand side is a list of fields in braces and an alias otherwise.
+fn parse_type_declaration(
+    p: &mut Parser,
+    location: Span,
+    visibility: Visibility,
+) -> Result<Node, ParseError> {
+    p.expect_keyword(vec![Keyword::TYPE])?;
+    p.advance_token();
+
+    let identifier = parse_identifier(p)?;
+
+    let generics = match p.current_token() {
+        Token::LessThan => parse_generic_params(p)?,
+        _ => Vec::new(),
+    };
+
+    p.expect_token(Token::Equal)?;
+    p.advance_token();
+
+    if *p.current_token() == Token::LBrace {
+        let fields = parse_record_fields(p)?;
+        p.expect_token(Token::Semi)?;
+        let semi = parse_semi(p)?;
+        return Ok(Node::Record(RecordNode {
+            span: p.span_from(location),
+            docs: None,
+            visibility,
+            identifier: Box::new(identifier),
+            generics,
+            fields,
+            semi: Box::new(semi),
+        }));
+    }
+
+    let aliased = parse_type(p)?;
+
+    p.expect_token(Token::Semi)?;
+    let semi = parse_semi(p)?;
+
+    Ok(Node::TypeAlias(TypeAliasNode {
+        span: p.span_from(location),
+        docs: None,
+        visibility,
+        identifier: Box::new(identifier),
+        generics,
+        aliased: Box::new(aliased),
+        semi: Box::new(semi),
+    }))
+}
+
+fn parse_record_fields(p: &mut Parser) -> Result<Vec<Node>, ParseError> {
+    p.expect_token(Token::LBrace)?;
+    p.advance_token();
+
+    let mut fields: Vec<Node> = Vec::new();
+    while *p.current_token() != Token::RBrace {
+        let location = p.span();
+        let identifier = parse_identifier(p)?;
+
+        p.expect_token(Token::Colon)?;
+        p.advance_token();
+
+        let type_annotation = parse_type(p)?;
+
+        fields.push(Node::Field(FieldNode {
+            span: p.span_from(location),
+            identifier: Box::new(identifier),
+            type_annotation: Box::new(type_annotation),
+        }));
+
+        if *p.current_token() == Token::Comma {
+            p.advance_token();
+        } else {
+            break;
+        }
+    }
+
+    p.expect_token(Token::RBrace)?;
+    p.advance_token();
+    Ok(fields)
+}
+
+fn parse_parameter(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+
+    let identifier = parse_identifier(p)?;
+
+    p.expect_token(Token::Colon)?;
+    p.advance_token();
+
+    let type_annotation = parse_type(p)?;
+
+    Ok(Node::Parameter(ParameterNode {
+        span: p.span_from(location),
+        identifier: Box::new(identifier),
+        type_annotation: Some(Box::new(type_annotation)),
+    }))
+}
+
+/// Parses `<T, U: Bound + Other>`.
+fn parse_generic_params(p: &mut Parser) -> Result<Vec<Node>, ParseError> {
+    p.expect_token(Token::LessThan)?;
+    p.advance_token();
+
+    let mut generics: Vec<Node> = Vec::new();
+    while !is_closing_angle(p.current_token()) {
+        let location = p.span();
+        let identifier = parse_identifier(p)?;
+
+        let bounds = match p.current_token() {
+            Token::Colon => {
+                p.advance_token();
+                parse_bounds(p)?
+            }
+            _ => Vec::new(),
+        };
+
+        generics.push(Node::GenericParam(GenericParamNode {
+            span: p.span_from(location),
+            identifier: Box::new(identifier),
+            bounds,
+        }));
+
+        if *p.current_token() == Token::Comma {
+            p.advance_token();
+        } else {
+            break;
+        }
+    }
+
+    parse_closing_angle(p)?;
+    Ok(generics)
+}
+
+fn is_closing_angle(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::GreaterThan | Token::ShiftRight | Token::GreaterThanEqual | Token::ShiftRightEqual
+    )
+}
+
+/// Consumes the `>` that closes a generic list. The lexer reads the end of
+/// `Vec<Vec<T>>` as one `>>`, and of `Vec<T>= x` as `>=`, so such a token
+/// is split and the rest of it is left as the current token.
+fn parse_closing_angle(p: &mut Parser) -> Result<(), ParseError> {
+    let rest = match p.current_token() {
+        Token::GreaterThan => {
+            p.advance_token();
+            return Ok(());
+        }
+        Token::ShiftRight => Token::GreaterThan,
+        Token::GreaterThanEqual => Token::Equal,
+        Token::ShiftRightEqual => Token::GreaterThanEqual,
+        _ => return Err(unexpected_token(p)),
+    };
+    let current = &mut p.current;
+    current.token = rest;
+    current.start += 1;
+    current.column += 1;
+    p.previous_end = current.start;
+    Ok(())
+}
+
+fn parse_block(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    p.expect_token(Token::LBrace)?;
+    p.advance_token();
+
+    let statements = p.with_struct_literals(true, parse_statements);
+
+    p.expect_token(Token::RBrace)?;
+    p.advance_token();
+
+    Ok(Node::Block(BlockNode {
+        span: p.span_from(location),
+        statements,
+    }))
+}
+
+fn parse_statements(p: &mut Parser) -> Vec<Node> {
+    let mut statements: Vec<Node> = Vec::new();
+    while !matches!(p.current_token(), Token::RBrace | Token::EOF) && !p.has_given_up() {
+        let statement_start = p.current_frame().start;
+        match parse_statement(p) {
+            Ok(statement) => statements.push(statement),
+            Err(err) => {
+                p.report_error(err);
+                synchronize(p, statement_start);
+            }
+        }
+    }
+    statements
+}
+
+// ----------------------------------------------------------------------
+// Patterns
+// ----------------------------------------------------------------------
+
+/// Parses a pattern, including `|` alternatives. A leading `|` is allowed.
+fn parse_pattern(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    if *p.current_token() == Token::Pipe {
+        p.advance_token();
+    }
+
+    let first = parse_primary_pattern(p)?;
+    if *p.current_token() != Token::Pipe {
+        return Ok(first);
+    }
+
+    let mut alternatives = vec![first];
+    while *p.current_token() == Token::Pipe {
+        p.advance_token();
+        alternatives.push(parse_primary_pattern(p)?);
+    }
+
+    Ok(Node::OrPattern(OrPatternNode {
+        span: p.span_from(location),
+        alternatives,
+    }))
+}
+
+fn parse_primary_pattern(p: &mut Parser) -> Result<Node, ParseError> {
+    match p.current_token() {
+        Token::Identifier(name) if name == "_" => {
+            let location = p.span();
+            p.advance_token();
+            Ok(Node::WildcardPattern(location))
+        }
+        Token::Range => {
+            // `..` skips any number of elements in a tuple or variant.
+            let location = p.span();
+            p.advance_token();
+            Ok(Node::RestPattern(location))
+        }
+        Token::Identifier(_) => parse_path_pattern(p),
+        Token::LParen => parse_tuple_pattern(p),
+        _ => parse_literal_pattern(p),
+    }
+}
+
+/// Parses a literal, optionally negated, and the range it may start.
+fn parse_literal_pattern(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+
+    let start = parse_pattern_literal(p)?;
+
+    let inclusive = match p.current_token() {
+        Token::Range => false,
+        Token::RangeInclusive => true,
+        _ => return Ok(start),
+    };
+    p.advance_token();
+
+    let end = parse_pattern_literal(p)?;
+
+    Ok(Node::RangePattern(RangePatternNode {
+        span: p.span_from(location),
+        start: Box::new(start),
+        end: Box::new(end),
+        inclusive,
+    }))
+}
+
+fn parse_pattern_literal(p: &mut Parser) -> Result<Node, ParseError> {
+    if *p.current_token() != Token::Minus {
+        return parse_literal(p);
+    }
+
+    let location = p.span();
+    p.advance_token();
+    let operand = match p.current_token() {
+        Token::NumberLiteral(_) => parse_number_literal(p),
+        _ => Err(unexpected_token(p)),
+    }?;
+
+    Ok(Node::Unary(UnaryNode {
+        span: p.span_from(location),
+        operator: UnaryOperator::Negate,
+        operand: Box::new(operand),
+    }))
+}
+
+/// Parses a pattern that starts with a name: a binding, an enum variant with
+/// or without arguments, or a struct pattern.
+fn parse_path_pattern(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+
+    let mut path = vec![parse_identifier(p)?];
+    while matches!(p.current_token(), Token::Dot | Token::DblColon) {
+        p.advance_token();
+        path.push(parse_identifier(p)?);
+    }
+
+    match p.current_token() {
+        Token::LBrace => parse_struct_pattern(p, location, path),
+        Token::LParen => {
+            let arguments = parse_pattern_list(p)?;
+            Ok(Node::VariantPattern(VariantPatternNode {
+                span: p.span_from(location),
+                path,
+                arguments: Some(arguments),
+            }))
+        }
+        _ if path.len() == 1 => Ok(path.remove(0)),
+        _ => Ok(Node::VariantPattern(VariantPatternNode {
+            span: p.span_from(location),
+            path,
+            arguments: None,
+        })),
+    }
+}
+
+fn parse_struct_pattern(
+    p: &mut Parser,
+    location: Span,
+    path: Vec<Node>,
+) -> Result<Node, ParseError> {
+    p.expect_token(Token::LBrace)?;
+    p.advance_token();
+
+    let mut fields: Vec<Node> = Vec::new();
+    let mut has_rest = false;
+    while *p.current_token() != Token::RBrace {
+        if *p.current_token() == Token::Range {
+            // `..` ignores the remaining fields and must come last.
+            p.advance_token();
+            has_rest = true;
+            break;
+        }
+
+        let field_location = p.span();
+        let identifier = parse_identifier(p)?;
+        let pattern = match p.current_token() {
+            Token::Colon => {
+                p.advance_token();
+                Some(Box::new(parse_pattern(p)?))
+            }
+            _ => None,
+        };
+        fields.push(Node::FieldPattern(FieldPatternNode {
+            span: p.span_from(field_location),
+            identifier: Box::new(identifier),
+            pattern,
+        }));
+
+        if *p.current_token() == Token::Comma {
+            p.advance_token();
+        } else {
+            break;
+        }
+    }
+
+    p.expect_token(Token::RBrace)?;
+    p.advance_token();
+
+    Ok(Node::StructPattern(StructPatternNode {
+        span: p.span_from(location),
+        path,
+        fields,
+        has_rest,
+    }))
+}
+
+/// Parses `(a, b)`. A single pattern in parentheses without a trailing
+/// comma is just that pattern.
+fn parse_tuple_pattern(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    p.expect_token(Token::LParen)?;
+    p.advance_token();
+
+    let mut elements: Vec<Node> = Vec::new();
+    let mut has_trailing_comma = false;
+    while *p.current_token() != Token::RParen {
+        elements.push(parse_pattern(p)?);
+        has_trailing_comma = *p.current_token() == Token::Comma;
+        if has_trailing_comma {
+            p.advance_token();
+        } else {
+            break;
+        }
+    }
+
+    p.expect_token(Token::RParen)?;
+    p.advance_token();
+
+    if elements.len() == 1 && !has_trailing_comma {
+        return Ok(elements.remove(0));
+    }
+
+    Ok(Node::TuplePattern(TuplePatternNode {
+        span: p.span_from(location),
+        elements,
+    }))
+}
+
+fn parse_pattern_list(p: &mut Parser) -> Result<Vec<Node>, ParseError> {
+    p.expect_token(Token::LParen)?;
+    p.advance_token();
+
+    let mut patterns: Vec<Node> = Vec::new();
+    while *p.current_token() != Token::RParen {
+        patterns.push(parse_pattern(p)?);
+        if *p.current_token() == Token::Comma {
+            p.advance_token();
+        } else {
+            break;
+        }
+    }
+
+    p.expect_token(Token::RParen)?;
+    p.advance_token();
+    Ok(patterns)
+}
+
+// ----------------------------------------------------------------------
+// Types
+// ----------------------------------------------------------------------
+
+/// Parses a type, including `|` unions. A leading `|` is allowed so long
+/// unions can be written one member per line.
+fn parse_type(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    if *p.current_token() == Token::Pipe {
+        p.advance_token();
+    }
+
+    let first = parse_primary_type(p)?;
+    if *p.current_token() != Token::Pipe {
+        return Ok(first);
+    }
+
+    let mut types = vec![first];
+    while *p.current_token() == Token::Pipe {
+        p.advance_token();
+        types.push(parse_primary_type(p)?);
+    }
+
+    Ok(Node::UnionType(UnionTypeNode {
+        span: p.span_from(location),
+        types,
+    }))
+}
+
+fn parse_primary_type(p: &mut Parser) -> Result<Node, ParseError> {
+    match p.current_token() {
+        Token::Keyword(Keyword::VOID) => {
+            let location = p.span();
+            p.advance_token();
+            Ok(Node::VoidType(location))
+        }
+        Token::Amp => parse_reference_type(p),
+        Token::LBracket => parse_array_type(p),
+        Token::LParen => parse_parenthesized_type(p, true),
+        _ => parse_named_type(p),
+    }
+}
+
+fn parse_reference_type(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    p.expect_token(Token::Amp)?;
+    p.advance_token();
+
+    let referent = parse_primary_type(p)?;
+
+    Ok(Node::ReferenceType(ReferenceTypeNode {
+        span: p.span_from(location),
+        referent: Box::new(referent),
+    }))
+}
+
+fn parse_array_type(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    p.expect_token(Token::LBracket)?;
+    p.advance_token();
+
+    let element = parse_type(p)?;
+
+    let length = match p.current_token() {
+        Token::Semi => {
+            p.advance_token();
+            Some(Box::new(parse_expression(p)?))
+        }
+        _ => None,
+    };
+
+    p.expect_token(Token::RBracket)?;
+    p.advance_token();
+
+    Ok(Node::ArrayType(ArrayTypeNode {
+        span: p.span_from(location),
+        element: Box::new(element),
+        length,
+    }))
+}
+
+/// Parses a tuple, a parenthesized type or, if `is_function_allowed`, a
+/// function type `(A, B) => R`.
+fn parse_parenthesized_type(p: &mut Parser, is_function_allowed: bool) -> Result<Node, ParseError> {
+    let location = p.span();
+    p.expect_token(Token::LParen)?;
+    p.advance_token();
+
+    let mut elements: Vec<Node> = Vec::new();
+    let mut has_trailing_comma = false;
+    while *p.current_token() != Token::RParen {
+        elements.push(parse_type(p)?);
+        has_trailing_comma = *p.current_token() == Token::Comma;
+        if has_trailing_comma {
+            p.advance_token();
+        } else {
+            break;
+        }
+    }
+
+    p.expect_token(Token::RParen)?;
+    p.advance_token();
+
+    if is_function_allowed && *p.current_token() == Token::FatArrow {
+        p.advance_token();
+        let return_type = parse_type(p)?;
+        return Ok(Node::FunctionType(FunctionTypeNode {
+            span: p.span_from(location),
+            parameters: elements,
+            return_type: Box::new(return_type),
+        }));
+    }
+
+    if elements.len() == 1 && !has_trailing_comma {
+        return Ok(elements.remove(0));
+    }
+
+    Ok(Node::TupleType(TupleTypeNode {
+        span: p.span_from(location),
+        elements,
+    }))
+}
+
+fn parse_named_type(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+
+    let identifier = parse_path(p)?;
+
+    let mut arguments: Vec<Node> = Vec::new();
+    if *p.current_token() == Token::LessThan {
+        p.advance_token();
+        while !is_closing_angle(p.current_token()) {
+            arguments.push(parse_type(p)?);
+            if *p.current_token() == Token::Comma {
+                p.advance_token();
+            } else {
+                break;
+            }
+        }
+        parse_closing_angle(p)?;
+    }
+
+    Ok(Node::NamedType(NamedTypeNode {
+        span: p.span_from(location),
+        identifier: Box::new(identifier),
+        arguments,
+    }))
+}
+
+fn parse_variable_statement(
+    p: &mut Parser,
+    location: Span,
+    visibility: Visibility,
+) -> Result<Node, ParseError> {
+    p.expect_keyword(vec![Keyword::LET, Keyword::CONST])?;
+    let keyword = parse_keyword(p)?;
+
+    let identifier = parse_identifier(p)?;
+
+    let type_annotation = match p.current_token() {
+        Token::Colon => {
+            p.advance_token();
+            Some(Box::new(parse_type(p)?))
+        }
+        _ => None,
+    };
+
+    p.expect_token(Token::Equal)?;
+    p.advance_token();
+
+    let initializer = parse_expression(p)?;
+
+    let semi = match p.current_token() {
+        Token::Semi => parse_semi(p),
+        _ => Err(unexpected_token(p)),
+    }?;
+
+    Ok(Node::Variable(VariableNode {
+        span: p.span_from(location),
+        docs: None,
+        visibility,
+        keyword: Box::new(keyword),
+        identifier: Box::new(identifier),
+        type_annotation,
+        initializer: Box::new(initializer),
+        semi: Box::new(semi),
+    }))
+}
+
+fn parse_keyword(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    let keyword = match p.current_token() {
+        Token::Keyword(keyword) => Ok(keyword.clone()),
+        _ => Err(unexpected_token(p)),
+    }?;
+    p.advance_token();
+    Ok(Node::Keyword(KeywordNode {
+        span: location,
+        keyword,
+    }))
+}
+
+/// Parses a name qualified by its module, `a::b::c`, or a bare identifier.
+fn parse_path(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+
+    let first = parse_identifier(p)?;
+    if *p.current_token() != Token::DblColon {
+        return Ok(first);
+    }
+
+    let mut segments = vec![first];
+    while *p.current_token() == Token::DblColon {
+        p.advance_token();
+        segments.push(parse_identifier(p)?);
+    }
+
+    Ok(Node::Path(PathNode {
+        span: p.span_from(location),
+        segments,
+    }))
+}
+
+fn parse_identifier(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    let identifier = match p.current_token() {
+        Token::Identifier(identifier) => Ok(identifier.clone()),
+        _ => Err(unexpected_token(p)),
+    }?;
+    p.advance_token();
+    Ok(Node::Ident(IdentNode {
+        span: location,
+        identifier,
+    }))
+}
+
+// ----------------------------------------------------------------------
+// Expressions
+// ----------------------------------------------------------------------
+
+/// Binding power of the operators that can follow an expression, from
+/// loosest to tightest:
+///
+/// | Precedence  | Operators                              | Associativity |
+/// |-------------|----------------------------------------|---------------|
+/// | Assignment  | `a = b` `a += b` `a <<= b` ...         | right         |
+/// | Conditional | `a ? b : c`                            | right         |
+/// | Range       | `a..b` `a..=b`                         | left          |
+/// | Coalesce    | `??`                                   | left          |
+/// | LogicalOr   | `\|\|`                                 | left          |
+/// | LogicalAnd  | `&&`                                   | left          |
+/// | Comparison  | `==` `!=` `<` `<=` `>` `>=`            | left          |
+/// | BitOr       | `\|`                                   | left          |
+/// | BitXor      | `^`                                    | left          |
+/// | BitAnd      | `&`                                    | left          |
+/// | Shift       | `<<` `>>`                              | left          |
+/// | Sum         | `+` `-`                                | left          |
+/// | Product     | `*` `/` `%`                            | left          |
+/// | Prefix      | `!a` `-a` `await a`                    | right         |
+/// | Postfix     | `a(..)` `a[..]` `a.b` `a?.b` `a.await` | left          |
+///
+/// As in Rust, the bitwise operators bind tighter than comparisons, so
+/// `a & b == c` is `(a & b) == c`.
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+enum Precedence {
+    Lowest,
+    Assignment,
+    Conditional,
+    Range,
+    Coalesce,
+    LogicalOr,
+    LogicalAnd,
+    Comparison,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Shift,
+    Sum,
+    Product,
+    Prefix,
+    Postfix,
+}
+
+fn infix_precedence(token: &Token) -> Precedence {
+    match token {
+        Token::Equal
+        | Token::PlusEqual
+        | Token::MinusEqual
+        | Token::MultiplyEqual
+        | Token::DivideEqual
+        | Token::PercentEqual
+        | Token::AmpEqual
+        | Token::PipeEqual
+        | Token::CaretEqual
+        | Token::ShiftLeftEqual
+        | Token::ShiftRightEqual => Precedence::Assignment,
+        Token::Question => Precedence::Conditional,
+        Token::Range | Token::RangeInclusive => Precedence::Range,
+        Token::DblQuestion => Precedence::Coalesce,
+        Token::LogicalOr => Precedence::LogicalOr,
+        Token::LogicalAnd => Precedence::LogicalAnd,
+        Token::EqualTo
+        | Token::NotEqualTo
+        | Token::LessThan
+        | Token::LessThanEqual
+        | Token::GreaterThan
+        | Token::GreaterThanEqual => Precedence::Comparison,
+        Token::Pipe => Precedence::BitOr,
+        Token::Caret => Precedence::BitXor,
+        Token::Amp => Precedence::BitAnd,
+        Token::ShiftLeft | Token::ShiftRight => Precedence::Shift,
+        Token::Plus | Token::Minus => Precedence::Sum,
+        Token::Asterisk | Token::FSlash | Token::Percent => Precedence::Product,
+        Token::LParen | Token::LBracket | Token::Dot | Token::QuestionDot => Precedence::Postfix,
+        _ => Precedence::Lowest,
+    }
+}
+
+fn binary_operator(token: &Token) -> Option<BinaryOperator> {
+    let operator = match token {
+        Token::Plus => BinaryOperator::Add,
+        Token::Minus => BinaryOperator::Subtract,
+        Token::Asterisk => BinaryOperator::Multiply,
+        Token::FSlash => BinaryOperator::Divide,
+        Token::Percent => BinaryOperator::Remainder,
+        Token::Amp => BinaryOperator::BitAnd,
+        Token::Pipe => BinaryOperator::BitOr,
+        Token::Caret => BinaryOperator::BitXor,
+        Token::ShiftLeft => BinaryOperator::ShiftLeft,
+        Token::ShiftRight => BinaryOperator::ShiftRight,
+        Token::EqualTo => BinaryOperator::EqualTo,
+        Token::NotEqualTo => BinaryOperator::NotEqualTo,
+        Token::LessThan => BinaryOperator::LessThan,
+        Token::LessThanEqual => BinaryOperator::LessThanEqual,
+        Token::GreaterThan => BinaryOperator::GreaterThan,
+        Token::GreaterThanEqual => BinaryOperator::GreaterThanEqual,
+        Token::LogicalAnd => BinaryOperator::LogicalAnd,
+        Token::LogicalOr => BinaryOperator::LogicalOr,
+        Token::DblQuestion => BinaryOperator::Coalesce,
+        _ => return None,
+    };
+    Some(operator)
+}
+
+fn assign_operator(token: &Token) -> Option<AssignOperator> {
+    let operator = match token {
+        Token::Equal => AssignOperator::Assign,
+        Token::PlusEqual => AssignOperator::Add,
+        Token::MinusEqual => AssignOperator::Subtract,
+        Token::MultiplyEqual => AssignOperator::Multiply,
+        Token::DivideEqual => AssignOperator::Divide,
+        Token::PercentEqual => AssignOperator::Remainder,
+        Token::AmpEqual => AssignOperator::BitAnd,
+        Token::PipeEqual => AssignOperator::BitOr,
+        Token::CaretEqual => AssignOperator::BitXor,
+        Token::ShiftLeftEqual => AssignOperator::ShiftLeft,
+        Token::ShiftRightEqual => AssignOperator::ShiftRight,
+        _ => return None,
+    };
+    Some(operator)
+}
+
+fn parse_expression(p: &mut Parser) -> Result<Node, ParseError> {
+    parse_expression_with(p, Precedence::Lowest)
+}
+
+/// Parses an expression made of operators that bind tighter than
+/// `precedence`.
+fn parse_expression_with(p: &mut Parser, precedence: Precedence) -> Result<Node, ParseError> {
+    let location = p.span();
+    let mut left = parse_prefix_expression(p)?;
+
+    while infix_precedence(p.current_token()) > precedence {
+        left = match p.current_token() {
+            token if assign_operator(token).is_some() => {
+                parse_assign_expression(p, location, left)?
+            }
+            Token::Question => parse_conditional_expression(p, location, left)?,
+            Token::Range | Token::RangeInclusive => parse_range_expression(p, location, left)?,
+            Token::LParen => parse_call_expression(p, location, left)?,
+            Token::LBracket => parse_index_expression(p, location, left)?,
+            Token::Dot | Token::QuestionDot => parse_member_expression(p, location, left)?,
+            _ => parse_binary_expression(p, location, left)?,
+        };
+    }
+
+    Ok(left)
+}
+
+fn parse_prefix_expression(p: &mut Parser) -> Result<Node, ParseError> {
+    match p.current_token() {
+        Token::Identifier(_) => {
+            let location = p.span();
+            let path = parse_path(p)?;
+            match p.current_token() {
+                Token::LBrace if p.is_struct_allowed => parse_struct_expression(p, location, path),
+                _ => Ok(path),
+            }
+        }
+        Token::LBracket => parse_array_expression(p),
+        Token::Bang | Token::Minus => parse_unary_expression(p),
+        Token::Pipe | Token::LogicalOr => parse_closure(p, p.span(), false),
+        Token::LParen if p.is_arrow_closure_allowed && is_arrow_closure(p) => {
+            parse_closure(p, p.span(), false)
+        }
+        Token::LParen => parse_group_expression(p),
+        Token::Keyword(Keyword::MATCH) => parse_match_expression(p),
+        Token::Keyword(Keyword::ASYNC) if *p.peek_token() == Token::LBrace => parse_async_block(p),
+        Token::Keyword(Keyword::ASYNC) => {
+            let location = p.span();
+            p.advance_token();
+            parse_closure(p, location, true)
+        }
+        Token::Keyword(Keyword::AWAIT) => parse_await_expression(p),
+        Token::Keyword(Keyword::SELF) => {
+            let location = p.span();
+            p.advance_token();
+            Ok(Node::SelfValue(location))
+        }
+        _ => parse_literal(p),
+    }
+}
+
+fn parse_unary_expression(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    let operator = match p.current_token() {
+        Token::Bang => UnaryOperator::Not,
+        Token::Minus => UnaryOperator::Negate,
+        _ => return Err(unexpected_token(p)),
+    };
+    p.advance_token();
+
+    let operand = parse_expression_with(p, Precedence::Prefix)?;
+
+    Ok(Node::Unary(UnaryNode {
+        span: p.span_from(location),
+        operator,
+        operand: Box::new(operand),
+    }))
+}
+
+fn parse_async_block(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    p.expect_keyword(vec![Keyword::ASYNC])?;
+    p.advance_token();
+
+    // As with a function body, `break` cannot leave an async block.
+    let enclosing_loops = std::mem::take(&mut p.loops);
+    let body = parse_block(p);
+    p.loops = enclosing_loops;
+    let body = body?;
+
+    Ok(Node::AsyncBlock(AsyncBlockNode {
+        span: p.span_from(location),
+        body: Box::new(body),
+    }))
+}
+
+fn parse_await_expression(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    p.expect_keyword(vec![Keyword::AWAIT])?;
+    p.advance_token();
+
+    let expression = parse_expression_with(p, Precedence::Prefix)?;
+
+    Ok(Node::Await(AwaitNode {
+        span: p.span_from(location),
+        expression: Box::new(expression),
+    }))
+}
+
+/// Decides whether the current `(` opens the parameters of an arrow
+/// closure rather than a group: the matching `)` has to be followed by
+/// `=>`, or by a return type and then `=>`.
+fn is_arrow_closure(p: &Parser) -> bool {
+    let mut tokens = p
+        .peek
+        .iter()
+        .chain(p.iter.as_slice())
+        .map(|frame| &frame.token);
+
+    let mut depth = 1;
+    for token in tokens.by_ref() {
+        match token {
+            Token::LParen | Token::LBracket | Token::LBrace => depth += 1,
+            Token::RParen | Token::RBracket | Token::RBrace => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return false;
+    }
+
+    match tokens.next() {
+        Some(Token::FatArrow) => true,
+        Some(Token::Colon) => {
+            // Skip over anything that can make up a type.
+            let mut depth = 0;
+            for token in tokens {
+                match token {
+                    Token::FatArrow if depth == 0 => return true,
+                    Token::LParen | Token::LBracket | Token::LessThan => depth += 1,
+                    Token::RParen | Token::RBracket | Token::GreaterThan if depth > 0 => depth -= 1,
+                    Token::ShiftRight if depth > 1 => depth -= 2,
+                    Token::Identifier(_)
+                    | Token::Keyword(Keyword::VOID)
+                    | Token::Amp
+                    | Token::Pipe
+                    | Token::DblColon => {}
+                    Token::Comma | Token::Semi | Token::NumberLiteral(_) | Token::FatArrow
+                        if depth > 0 => {}
+                    _ => return false,
+                }
+            }
+            false
+        }
+        _ => false,
+    }
+}
+
+/// Parses a closure from its parameter list on; `location` is where it
+/// starts, which is before the `async` of an async closure.
+fn parse_closure(p: &mut Parser, location: Span, is_async: bool) -> Result<Node, ParseError> {
+    let is_arrow = *p.current_token() == Token::LParen;
+
+    let mut parameters: Vec<Node> = Vec::new();
+    match p.current_token() {
+        Token::LogicalOr => {
+            p.advance_token();
+        }
+        Token::Pipe | Token::LParen => {
+            let close = if is_arrow { Token::RParen } else { Token::Pipe };
+            p.advance_token();
+            while *p.current_token() != close {
+                parameters.push(parse_closure_parameter(p, is_arrow)?);
+                if *p.current_token() == Token::Comma {
+                    p.advance_token();
+                } else {
+                    break;
+                }
+            }
+            p.expect_token(close)?;
+            p.advance_token();
+        }
+        _ => return Err(unexpected_token(p)),
+    }
+
+    // In `(a): (A, B) => body` the `=>` starts the body, so a function type
+    // returned from an arrow closure has to be put in parentheses.
+    let return_type = match p.current_token() {
+        Token::Colon => {
+            p.advance_token();
+            let return_type = match p.current_token() {
+                Token::LParen if is_arrow => parse_parenthesized_type(p, false)?,
+                _ => parse_type(p)?,
+            };
+            Some(Box::new(return_type))
+        }
+        _ => None,
+    };
+
+    if is_arrow {
+        p.expect_token(Token::FatArrow)?;
+        p.advance_token();
+    } else if return_type.is_some() {
+        // As in Rust, a declared return type needs a block body.
+        p.expect_token(Token::LBrace)?;
+    }
+
+    let enclosing_loops = std::mem::take(&mut p.loops);
+    let body = match p.current_token() {
+        Token::LBrace => parse_block(p),
+        _ => parse_expression(p),
+    };
+    p.loops = enclosing_loops;
+    let body = body?;
+
+    Ok(Node::Closure(ClosureNode {
+        span: p.span_from(location),
+        is_async,
+        parameters,
+        return_type,
+        body: Box::new(body),
+    }))
+}
+
+/// A closure parameter, whose type is optional. Between pipes the type
+/// cannot be a bare union, since its `|` would end the parameter list.
+fn parse_closure_parameter(p: &mut Parser, is_arrow: bool) -> Result<Node, ParseError> {
+    let location = p.span();
+
+    let identifier = parse_identifier(p)?;
+
+    let type_annotation = match p.current_token() {
+        Token::Colon => {
+            p.advance_token();
+            let type_annotation = if is_arrow {
+                parse_type(p)?
+            } else {
+                parse_primary_type(p)?
+            };
+            Some(Box::new(type_annotation))
+        }
+        _ => None,
+    };
+
+    Ok(Node::Parameter(ParameterNode {
+        span: p.span_from(location),
+        identifier: Box::new(identifier),
+        type_annotation,
+    }))
+}
+
+/// Parses `(a)`, or a tuple if there is a comma or nothing between the
+/// parentheses: `(a, b)`, `(a,)`, `()`.
+fn parse_group_expression(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    p.expect_token(Token::LParen)?;
+    p.advance_token();
+
+    let mut elements: Vec<Node> = Vec::new();
+    let mut has_trailing_comma = false;
+    while *p.current_token() != Token::RParen {
+        elements.push(p.with_struct_literals(true, parse_expression)?);
+        has_trailing_comma = *p.current_token() == Token::Comma;
+        if has_trailing_comma {
+            p.advance_token();
+        } else {
+            break;
+        }
+    }
+
+    p.expect_token(Token::RParen)?;
+    p.advance_token();
+
+    if elements.len() == 1 && !has_trailing_comma {
+        return Ok(Node::Group(GroupNode {
+            span: p.span_from(location),
+            expression: Box::new(elements.remove(0)),
+        }));
+    }
+
+    Ok(Node::Tuple(TupleNode {
+        span: p.span_from(location),
+        elements,
+    }))
+}
+
+/// Parses `[a, b, c]` or `[value; length]`.
+fn parse_array_expression(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    p.expect_token(Token::LBracket)?;
+    p.advance_token();
+
+    if *p.current_token() == Token::RBracket {
+        p.advance_token();
+        return Ok(Node::Array(ArrayNode {
+            span: p.span_from(location),
+            elements: Vec::new(),
+        }));
+    }
+
+    let first = p.with_struct_literals(true, parse_expression)?;
+
+    if *p.current_token() == Token::Semi {
+        p.advance_token();
+        let length = p.with_struct_literals(true, parse_expression)?;
+        p.expect_token(Token::RBracket)?;
+        p.advance_token();
+        return Ok(Node::ArrayRepeat(ArrayRepeatNode {
+            span: p.span_from(location),
+            value: Box::new(first),
+            length: Box::new(length),
+        }));
+    }
+
+    let mut elements = vec![first];
+    if *p.current_token() == Token::Comma {
+        p.advance_token();
+        elements.append(&mut parse_expression_list(p, Token::RBracket)?);
+    } else {
+        p.expect_token(Token::RBracket)?;
+        p.advance_token();
+    }
+
+    Ok(Node::Array(ArrayNode {
+        span: p.span_from(location),
+        elements,
+    }))
+}
+
+/// Parses the braces of `Path { x: 1, y, ..base }` once `path` is known.
+fn parse_struct_expression(p: &mut Parser, location: Span, path: Node) -> Result<Node, ParseError> {
+    p.expect_token(Token::LBrace)?;
+    p.advance_token();
+
+    let mut fields: Vec<Node> = Vec::new();
+    let mut base = None;
+    while *p.current_token() != Token::RBrace {
+        if *p.current_token() == Token::Range {
+            // The base has to come last, without a comma after it.
+            p.advance_token();
+            base = Some(Box::new(p.with_struct_literals(true, parse_expression)?));
+            break;
+        }
+
+        fields.push(parse_field_init(p)?);
+        if *p.current_token() == Token::Comma {
+            p.advance_token();
+        } else {
+            break;
+        }
+    }
+
+    p.expect_token(Token::RBrace)?;
+    p.advance_token();
+
+    Ok(Node::Struct(StructNode {
+        span: p.span_from(location),
+        path: Box::new(path),
+        fields,
+        base,
+    }))
+}
+
+fn parse_field_init(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+
+    let identifier = parse_identifier(p)?;
+
+    let value = match p.current_token() {
+        Token::Colon => {
+            p.advance_token();
+            Some(Box::new(p.with_struct_literals(true, parse_expression)?))
+        }
+        _ => None,
+    };
+
+    Ok(Node::FieldInit(FieldInitNode {
+        span: p.span_from(location),
+        identifier: Box::new(identifier),
+        value,
+    }))
+}
+
+fn parse_binary_expression(p: &mut Parser, location: Span, left: Node) -> Result<Node, ParseError> {
+    let precedence = infix_precedence(p.current_token());
+    let operator = binary_operator(p.current_token()).ok_or_else(|| unexpected_token(p))?;
+    p.advance_token();
+
+    let right = parse_expression_with(p, precedence)?;
+
+    Ok(Node::Binary(BinaryNode {
+        span: p.span_from(location),
+        operator,
+        left: Box::new(left),
+        right: Box::new(right),
+    }))
+}
+
+fn parse_assign_expression(
+    p: &mut Parser,
+    location: Span,
+    target: Node,
+) -> Result<Node, ParseError> {
+    let operator = assign_operator(p.current_token()).ok_or_else(|| unexpected_token(p))?;
+
+    // Reported without giving up, since the rest of the assignment still
+    // parses fine.
+    if !is_place_expression(&target) {
+        let target_span = p.span_from(location);
+        p.report_error(ParseError::InvalidAssignmentTarget(target_span));
+    }
+    p.advance_token();
+
+    // Parsing the value at the lowest precedence makes assignment right
+    // associative: `a = b = c` is `a = (b = c)`.
+    let value = parse_expression(p)?;
+
+    Ok(Node::Assign(AssignNode {
+        span: p.span_from(location),
+        operator,
+        target: Box::new(target),
+        value: Box::new(value),
+    }))
+}
+
+/// Whether `node` names a location that can be assigned to. Nothing in an
+/// optional chain can be, since `a?.b` may not exist.
+fn is_place_expression(node: &Node) -> bool {
+    match node {
+        Node::Ident(_) => true,
+        Node::Member(_) | Node::Index(_) => !is_optional_chain(node),
+        Node::Group(group) => is_place_expression(&group.expression),
+        _ => false,
+    }
+}
+
+/// Whether `node` has a `?.` anywhere along its chain: `a?.b.c[0]`.
+fn is_optional_chain(node: &Node) -> bool {
+    match node {
+        Node::Member(member) => member.is_optional || is_optional_chain(&member.object),
+        Node::Index(index) => is_optional_chain(&index.target),
+        Node::Call(call) => is_optional_chain(&call.callee),
+        _ => false,
+    }
+}
+
+fn parse_range_expression(p: &mut Parser, location: Span, start: Node) -> Result<Node, ParseError> {
+    let inclusive = *p.current_token() == Token::RangeInclusive;
+    p.advance_token();
+
+    let end = parse_expression_with(p, Precedence::Range)?;
+
+    Ok(Node::Range(RangeNode {
+        span: p.span_from(location),
+        start: Box::new(start),
+        end: Box::new(end),
+        inclusive,
+    }))
+}
+
+fn parse_conditional_expression(
+    p: &mut Parser,
+    location: Span,
+    condition: Node,
+) -> Result<Node, ParseError> {
+    p.expect_token(Token::Question)?;
+    p.advance_token();
+
+    let consequent = parse_expression(p)?;
+
+    p.expect_token(Token::Colon)?;
+    p.advance_token();
+
+    // Parsing the alternative at the lowest precedence makes `?:` right
+    // associative: `a ? b : c ? d : e` is `a ? b : (c ? d : e)`.
+    let alternative = parse_expression(p)?;
+
+    Ok(Node::Conditional(ConditionalNode {
+        span: p.span_from(location),
+        condition: Box::new(condition),
+        consequent: Box::new(consequent),
+        alternative: Box::new(alternative),
+    }))
+}
+
+fn parse_call_expression(p: &mut Parser, location: Span, callee: Node) -> Result<Node, ParseError> {
+    p.expect_token(Token::LParen)?;
+    p.advance_token();
+
+    let arguments = parse_expression_list(p, Token::RParen)?;
+
+    Ok(Node::Call(CallNode {
+        span: p.span_from(location),
+        callee: Box::new(callee),
+        arguments,
+    }))
+}
+
+fn parse_index_expression(
+    p: &mut Parser,
+    location: Span,
+    target: Node,
+) -> Result<Node, ParseError> {
+    p.expect_token(Token::LBracket)?;
+    p.advance_token();
+
+    let index = p.with_struct_literals(true, parse_expression)?;
+
+    p.expect_token(Token::RBracket)?;
+    p.advance_token();
+
+    Ok(Node::Index(IndexNode {
+        span: p.span_from(location),
+        target: Box::new(target),
+        index: Box::new(index),
+    }))
+}
+
+fn parse_member_expression(
+    p: &mut Parser,
+    location: Span,
+    object: Node,
+) -> Result<Node, ParseError> {
+    let is_optional = match p.current_token() {
+        Token::Dot => false,
+        Token::QuestionDot => true,
+        _ => return Err(unexpected_token(p)),
+    };
+    p.advance_token();
+
+    if !is_optional && *p.current_token() == Token::Keyword(Keyword::AWAIT) {
+        p.advance_token();
+        return Ok(Node::Await(AwaitNode {
+            span: p.span_from(location),
+            expression: Box::new(object),
+        }));
+    }
+
+    let property = parse_identifier(p)?;
+
+    Ok(Node::Member(MemberNode {
+        span: p.span_from(location),
+        object: Box::new(object),
+        property: Box::new(property),
+        is_optional,
+    }))
+}
+
+fn parse_match_expression(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    p.expect_keyword(vec![Keyword::MATCH])?;
+    p.advance_token();
+
+    let subject = p.with_struct_literals(false, parse_expression)?;
+
+    p.expect_token(Token::LBrace)?;
+    p.advance_token();
+
+    let mut arms: Vec<Node> = Vec::new();
+    while *p.current_token() != Token::RBrace {
+        let arm = parse_match_arm(p)?;
+        let has_block_body =
+            matches!(&arm, Node::MatchArm(arm) if matches!(*arm.body, Node::Block(_)));
+        arms.push(arm);
+
+        // Arms are separated by commas, which are optional after a block.
+        match p.current_token() {
+            Token::Comma => {
+                p.advance_token();
+            }
+            Token::RBrace => break,
+            _ if has_block_body => {}
+            _ => return Err(unexpected_token(p)),
+        }
+    }
+
+    p.expect_token(Token::RBrace)?;
+    p.advance_token();
+
+    Ok(Node::Match(MatchNode {
+        span: p.span_from(location),
+        subject: Box::new(subject),
+        arms,
+    }))
+}
+
+fn parse_match_arm(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+
+    let pattern = parse_pattern(p)?;
+
+    let guard = match p.current_token() {
+        Token::Keyword(Keyword::IF) => {
+            p.advance_token();
+            Some(Box::new(p.with_arrow_closures(false, parse_expression)?))
+        }
+        _ => None,
+    };
+
+    p.expect_token(Token::FatArrow)?;
+    p.advance_token();
+
+    let body = match p.current_token() {
+        Token::LBrace => parse_block(p),
+        _ => parse_expression(p),
+    }?;
+
+    Ok(Node::MatchArm(MatchArmNode {
+        span: p.span_from(location),
+        pattern: Box::new(pattern),
+        guard,
+        body: Box::new(body),
+    }))
+}
+
+/// Parses comma separated expressions up to and including `close`. A
+/// trailing comma is allowed.
+fn parse_expression_list(p: &mut Parser, close: Token) -> Result<Vec<Node>, ParseError> {
+    let mut expressions: Vec<Node> = Vec::new();
+    while *p.current_token() != close {
+        let expression =
+            p.with_arrow_closures(true, |p| p.with_struct_literals(true, parse_expression));
+        expressions.push(expression?);
+        if *p.current_token() == Token::Comma {
+            p.advance_token();
+        } else {
+            break;
+        }
+    }
+    p.expect_token(close)?;
+    p.advance_token();
+    Ok(expressions)
+}
+
+fn parse_literal(p: &mut Parser) -> Result<Node, ParseError> {
+    match p.current_token() {
+        Token::NumberLiteral(_) => parse_number_literal(p),
+        Token::StringLiteral(_) => parse_string_literal(p),
+        Token::ByteStringLiteral(_) => parse_byte_string_literal(p),
+        Token::CharLiteral(_) => parse_char_literal(p),
+        Token::BoolLiteral(_) => parse_bool_literal(p),
+        Token::TemplateLiteral(_) | Token::TemplateHead(_) => parse_template_literal(p),
+        _ => Err(unexpected_token(p)),
+    }
+}
+
+fn parse_string_literal(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    let value = match p.current_token() {
+        Token::StringLiteral(literal) => Ok(literal.clone()),
+        _ => Err(unexpected_token(p)),
+    }?;
+    p.advance_token();
+    Ok(Node::String(StringNode {
+        span: location,
+        value,
+    }))
+}
+
+fn parse_byte_string_literal(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    let value = match p.current_token() {
+        Token::ByteStringLiteral(literal) => Ok(literal.clone()),
+        _ => Err(unexpected_token(p)),
+    }?;
+    p.advance_token();
+    Ok(Node::ByteString(ByteStringNode {
+        span: location,
+        value,
+    }))
+}
+
+fn parse_char_literal(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    let value = match p.current_token() {
+        Token::CharLiteral(literal) => Ok(*literal),
+        _ => Err(unexpected_token(p)),
+    }?;
+    p.advance_token();
+    Ok(Node::Char(CharNode {
+        span: location,
+        value,
+    }))
+}
+
+fn parse_bool_literal(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    let value = match p.current_token() {
+        Token::BoolLiteral(literal) => Ok(*literal),
+        _ => Err(unexpected_token(p)),
+    }?;
+    p.advance_token();
+    Ok(Node::Bool(BoolNode {
+        span: location,
+        value,
+    }))
+}
+
+fn parse_template_literal(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    let mut strings: Vec<String> = Vec::new();
+    let mut expressions: Vec<Node> = Vec::new();
+
+    match p.current_token() {
+        Token::TemplateLiteral(chunk) => {
+            strings.push(chunk.clone());
+            p.advance_token();
+        }
+        Token::TemplateHead(chunk) => {
+            strings.push(chunk.clone());
+            p.advance_token();
+            loop {
+                expressions.push(p.with_struct_literals(true, parse_expression)?);
+                match p.current_token() {
+                    Token::TemplateMiddle(chunk) => {
+                        strings.push(chunk.clone());
+                        p.advance_token();
+                    }
+                    Token::TemplateTail(chunk) => {
+                        strings.push(chunk.clone());
+                        p.advance_token();
+                        break;
+                    }
+                    _ => return Err(unexpected_token(p)),
+                }
+            }
+        }
+        _ => return Err(unexpected_token(p)),
+    }
+
+    Ok(Node::Template(TemplateNode {
+        span: p.span_from(location),
+        strings,
+        expressions,
+    }))
+}
+
+fn parse_number_literal(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    let number = match p.current_token() {
+        Token::NumberLiteral(literal) => Ok(literal.clone()),
+        _ => Err(unexpected_token(p)),
+    }?;
+    p.advance_token();
+    Ok(Node::Number(NumberNode {
+        span: location,
+        parsed: number.parse_value(),
+        kind: number.kind,
+        postfix: number.postfix,
+        value: number.value,
+    }))
+}
+
+fn parse_semi(p: &mut Parser) -> Result<Node, ParseError> {
+    let location = p.span();
+    p.advance_token();
     Ok(Node::Semi(location))
 }
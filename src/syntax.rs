@@ -0,0 +1,422 @@
+use crate::ast::*;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::token::*;
+use std::fmt;
+use std::iter::Peekable;
+use std::ops::Range;
+use std::rc::Rc;
+use std::vec::IntoIter;
+
+/// Name of the node at the root of every tree.
+pub const PROGRAM_KIND: &str = "Program";
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TriviaKind {
+    Whitespace,
+    /// A single `\n` or `\r\n`.
+    Newline,
+    Comment,
+    DocComment,
+}
+
+/// Source text that is kept in the tree but has no meaning for the grammar.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+}
+
+/// A token with the trivia around it. A token owns the trivia after it up
+/// to the end of its line, and everything before it back to the previous
+/// token's trailing trivia. Green elements do not know their position, so
+/// the same one may appear in several trees.
+#[derive(Debug, PartialEq)]
+pub struct GreenToken {
+    pub token: Token,
+    pub text: String,
+    pub leading: Vec<Trivia>,
+    pub trailing: Vec<Trivia>,
+}
+
+impl GreenToken {
+    /// Length in bytes, trivia included.
+    pub fn width(&self) -> usize {
+        trivia_width(&self.leading) + self.text.len() + trivia_width(&self.trailing)
+    }
+
+    fn write_text(&self, out: &mut String) {
+        for trivia in &self.leading {
+            out.push_str(&trivia.text);
+        }
+        out.push_str(&self.text);
+        for trivia in &self.trailing {
+            out.push_str(&trivia.text);
+        }
+    }
+}
+
+/// A node of the AST, by `Node::kind`, holding every token in its span.
+#[derive(Debug, PartialEq)]
+pub struct GreenNode {
+    pub kind: &'static str,
+    pub width: usize,
+    pub children: Vec<GreenElement>,
+}
+
+impl GreenNode {
+    pub fn new(kind: &'static str, children: Vec<GreenElement>) -> Self {
+        let width = children.iter().map(GreenElement::width).sum();
+        Self {
+            kind,
+            width,
+            children,
+        }
+    }
+
+    fn write_text(&self, out: &mut String) {
+        for child in &self.children {
+            match child {
+                GreenElement::Node(node) => node.write_text(out),
+                GreenElement::Token(token) => token.write_text(out),
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenElement {
+    pub fn width(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.width,
+            GreenElement::Token(token) => token.width(),
+        }
+    }
+}
+
+/// A green node placed in a tree: it knows its byte offset in the source
+/// and its parent. Red nodes are made on demand while walking down from
+/// the root and are cheap to clone.
+#[derive(Debug, Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+#[derive(Debug)]
+struct NodeData {
+    green: Rc<GreenNode>,
+    parent: Option<SyntaxNode>,
+    offset: usize,
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: Rc<GreenNode>) -> Self {
+        SyntaxNode(Rc::new(NodeData {
+            green,
+            parent: None,
+            offset: 0,
+        }))
+    }
+
+    pub fn kind(&self) -> &'static str {
+        self.0.green.kind
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+
+    pub fn parent(&self) -> Option<&SyntaxNode> {
+        self.0.parent.as_ref()
+    }
+
+    /// Byte range in the source, trivia included.
+    pub fn range(&self) -> Range<usize> {
+        self.0.offset..self.0.offset + self.0.green.width
+    }
+
+    pub fn children(&self) -> Vec<SyntaxElement> {
+        let mut offset = self.0.offset;
+        let mut children = Vec::new();
+        for child in &self.0.green.children {
+            children.push(match child {
+                GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                    green: green.clone(),
+                    parent: Some(self.clone()),
+                    offset,
+                }))),
+                GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                    green: green.clone(),
+                    parent: self.clone(),
+                    offset,
+                }),
+            });
+            offset += child.width();
+        }
+        children
+    }
+
+    pub fn child_nodes(&self) -> Vec<SyntaxNode> {
+        self.children()
+            .into_iter()
+            .filter_map(|child| match child {
+                SyntaxElement::Node(node) => Some(node),
+                SyntaxElement::Token(_) => None,
+            })
+            .collect()
+    }
+
+    /// Every token below this node, in source order.
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        for child in self.children() {
+            match child {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+
+    /// The source text covered by this node, exactly as it was written.
+    pub fn text(&self) -> String {
+        let mut text = String::with_capacity(self.0.green.width);
+        self.0.green.write_text(&mut text);
+        text
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    parent: SyntaxNode,
+    offset: usize,
+}
+
+impl SyntaxToken {
+    pub fn token(&self) -> &Token {
+        &self.green.token
+    }
+
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    pub fn leading(&self) -> &[Trivia] {
+        &self.green.leading
+    }
+
+    pub fn trailing(&self) -> &[Trivia] {
+        &self.green.trailing
+    }
+
+    pub fn parent(&self) -> &SyntaxNode {
+        &self.parent
+    }
+
+    /// Byte range of the token itself, without its trivia.
+    pub fn text_range(&self) -> Range<usize> {
+        let start = self.offset + trivia_width(&self.green.leading);
+        start..start + self.green.text.len()
+    }
+
+    /// Byte range in the source, trivia included.
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.green.width()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+/// The lossless tree for a source file, along with the AST it was shaped
+/// after.
+pub struct SyntaxTree {
+    pub root: SyntaxNode,
+    pub program: Program,
+}
+
+/// Parses `source` into a tree from which it can be printed back byte for
+/// byte. Each AST node becomes a node of the same kind owning the tokens in
+/// its span; tokens outside any statement, such as those skipped after an
+/// error, hang off the root. The last token is always an empty `EOF` that
+/// holds the trivia at the end of the file.
+pub fn parse_syntax_tree(source: &str) -> SyntaxTree {
+    let mut lexer = Lexer::new();
+    lexer.read_source(source);
+    lexer.finish();
+    let mut frames: Vec<TokenFrame> = Vec::new();
+    while let Some(frame) = lexer.next_token() {
+        frames.push(frame);
+    }
+
+    let mut program = Parser::new(frames.clone()).parse_program();
+    program.warnings = lexer.take_warnings();
+
+    let source: Vec<char> = source.chars().collect();
+    let mut builder = Builder {
+        tokens: collect_tokens(&source, frames).into_iter().peekable(),
+    };
+    let mut children: Vec<GreenElement> = Vec::new();
+    for statement in &program.statements {
+        builder.push_node(statement, &mut children);
+    }
+    builder.push_tokens_before(usize::MAX, &mut children);
+
+    SyntaxTree {
+        root: SyntaxNode::new_root(Rc::new(GreenNode::new(PROGRAM_KIND, children))),
+        program,
+    }
+}
+
+/// A token with the position it starts at in characters, which is how AST
+/// spans count.
+struct SpannedToken {
+    start: usize,
+    green: GreenToken,
+}
+
+struct Builder {
+    tokens: Peekable<IntoIter<SpannedToken>>,
+}
+
+impl Builder {
+    fn push_node(&mut self, node: &Node, children: &mut Vec<GreenElement>) {
+        let span = node.span();
+        self.push_tokens_before(span.start, children);
+
+        let mut node_children: Vec<GreenElement> = Vec::new();
+        for child in node.children() {
+            self.push_node(child, &mut node_children);
+        }
+        self.push_tokens_before(span.end, &mut node_children);
+
+        let green = GreenNode::new(node.kind(), node_children);
+        children.push(GreenElement::Node(Rc::new(green)));
+    }
+
+    /// Moves the tokens starting before `end` into `children`. Tokens are
+    /// only ever taken once, so a span that overlaps one already built
+    /// simply gets fewer tokens.
+    fn push_tokens_before(&mut self, end: usize, children: &mut Vec<GreenElement>) {
+        while let Some(token) = self.tokens.next_if(|token| token.start < end) {
+            children.push(GreenElement::Token(Rc::new(token.green)));
+        }
+    }
+}
+
+/// Turns the lexer's frames into green tokens, collecting the gaps between
+/// them and the comments as trivia.
+fn collect_tokens(source: &[char], frames: Vec<TokenFrame>) -> Vec<SpannedToken> {
+    let mut tokens: Vec<SpannedToken> = Vec::new();
+    let mut trivia: Vec<Trivia> = Vec::new();
+    let mut cursor = 0;
+
+    for frame in frames {
+        let start = frame.start.max(cursor);
+        let end = frame.end.max(start);
+        push_whitespace(&source[cursor..start], &mut trivia);
+        let text: String = source[start..end].iter().collect();
+        cursor = end;
+
+        match frame.token {
+            Token::Comment(_) | Token::MultilineComment(_) => trivia.push(Trivia {
+                kind: TriviaKind::Comment,
+                text,
+            }),
+            Token::DocComment(_) => trivia.push(Trivia {
+                kind: TriviaKind::DocComment,
+                text,
+            }),
+            token => push_token(&mut tokens, &mut trivia, token, text, start),
+        }
+    }
+
+    push_whitespace(&source[cursor..], &mut trivia);
+    let end = source.len();
+    push_token(&mut tokens, &mut trivia, Token::EOF, String::new(), end);
+    tokens
+}
+
+/// Adds a token to `tokens` with the pending `trivia` before it, after
+/// handing the part up to the first line break to the previous token.
+fn push_token(
+    tokens: &mut Vec<SpannedToken>,
+    trivia: &mut Vec<Trivia>,
+    token: Token,
+    text: String,
+    start: usize,
+) {
+    let mut leading = std::mem::take(trivia);
+    if let Some(previous) = tokens.last_mut() {
+        let line_end = leading
+            .iter()
+            .position(|trivia| trivia.kind == TriviaKind::Newline)
+            .unwrap_or(leading.len());
+        previous.green.trailing = leading.drain(..line_end).collect();
+    }
+
+    tokens.push(SpannedToken {
+        start,
+        green: GreenToken {
+            token,
+            text,
+            leading,
+            trailing: Vec::new(),
+        },
+    });
+}
+
+/// Splits the text between two tokens into line breaks and runs of other
+/// whitespace.
+fn push_whitespace(gap: &[char], trivia: &mut Vec<Trivia>) {
+    let mut run = String::new();
+    let mut chars = gap.iter().peekable();
+    while let Some(&ch) = chars.next() {
+        let newline = match ch {
+            '\n' => Some("\n"),
+            '\r' if chars.peek() == Some(&&'\n') => {
+                chars.next();
+                Some("\r\n")
+            }
+            _ => None,
+        };
+        match newline {
+            Some(newline) => {
+                if !run.is_empty() {
+                    trivia.push(Trivia {
+                        kind: TriviaKind::Whitespace,
+                        text: std::mem::take(&mut run),
+                    });
+                }
+                trivia.push(Trivia {
+                    kind: TriviaKind::Newline,
+                    text: newline.to_string(),
+                });
+            }
+            None => run.push(ch),
+        }
+    }
+    if !run.is_empty() {
+        trivia.push(Trivia {
+            kind: TriviaKind::Whitespace,
+            text: run,
+        });
+    }
+}
+
+fn trivia_width(trivia: &[Trivia]) -> usize {
+    trivia.iter().map(|trivia| trivia.text.len()).sum()
+}
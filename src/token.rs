@@ -8,33 +8,43 @@ pub enum Token {
     Keyword(Keyword),
     MultilineComment(String),
     Comment(String),
+    DocComment(String), // `/// text` or `/** text */`
 
     //
     // Literals
     //
     BoolLiteral(bool),
-    CharLiteral(String),
+    CharLiteral(char),
     NumberLiteral(Number),
     StringLiteral(String),
-    TemplateLiteral(String),
+    ByteStringLiteral(Vec<u8>), // b"text"
+    TemplateLiteral(String),    // `text` without interpolations
+    TemplateHead(String),       // `text${
+    TemplateMiddle(String),     // }text${
+    TemplateTail(String),       // }text`
 
     //
     // Delimiters + Operators
     //
     Amp,              // &
+    AmpEqual,         // &=
     Asterisk,         // *
     At,               // @
     BSlash,           // \
     Backtick,         // `
     Bang,             // !
     Caret,            // ^
+    CaretEqual,       // ^=
     Colon,            // :
     Comma,            // ,
+    DblColon,         // ::
+    DblQuestion,      // ??
     DblQuote,         // "
     DivideEqual,      // /=
     Dot,              // .
     Equal,            // =
     EqualTo,          // ==
+    FatArrow,         // =>
     FSlash,           // /
     GreaterThan,      // >
     GreaterThanEqual, // >=
@@ -50,24 +60,61 @@ pub enum Token {
     MultiplyEqual,    // *=
     NotEqualTo,       // !=
     Percent,          // %
+    PercentEqual,     // %=
     Pipe,             // |
+    PipeEqual,        // |=
     Plus,             // +
     PlusEqual,        // +=
     Question,         // ?
+    QuestionDot,      // ?.
+    Range,            // ..
+    RangeInclusive,   // ..=
     Quote,            // '
     RBrace,           // }
     RBracket,         // ]
     RParen,           // )
     Semi,             // ;
+    ShiftLeft,        // <<
+    ShiftLeftEqual,   // <<=
+    ShiftRight,       // >>
+    ShiftRightEqual,  // >>=
+    ThinArrow,        // ->
+}
+
+impl Token {
+    pub fn is_comment(&self) -> bool {
+        matches!(self, Token::Comment(_) | Token::MultilineComment(_))
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum TokenError {
     Illegal(char),
     UnterminatedCharLiteral,
+    EmptyCharLiteral,
+    OverlongCharLiteral,
+    InvalidEscape(String),
     UnterminatedStringLiteral,
+    NonAsciiInByteString(char),
+    UnterminatedMultilineComment,
+    UnterminatedTemplateLiteral,
     MalformedHexadecimal,
     MalformedDecimal,
+    MalformedBinary,
+    MalformedOctal,
+    InvalidNumberSuffix(String),
+}
+
+/// Something suspicious in otherwise valid source.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum TokenWarning {
+    /// `identifier` contains `character`, which is easily mistaken for
+    /// `looks_like`, such as a Cyrillic `а` in an otherwise Latin name.
+    ConfusableCharacter {
+        identifier: String,
+        character: char,
+        looks_like: String,
+    },
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -83,6 +130,7 @@ pub enum Keyword {
     FUNC,
     IF,
     IMPL,
+    IN,
     LET,
     MATCH,
     PUB,
@@ -99,15 +147,138 @@ pub enum Keyword {
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum NumberKind {
     Integer,
+    /// Has a fraction, an exponent or both: `1.5`, `1e10`, `1.5e-3`
     Decimal,
     Hexadecimal,
+    Binary,
+    Octal,
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Number {
     pub kind: NumberKind,
     pub value: String,
-    pub postfix: Option<String>,
+    pub postfix: Option<NumberSuffix>,
+}
+
+impl Number {
+    /// The magnitude of the literal, or None if it does not fit in a `u128`
+    /// or, for floats, an `f64`.
+    pub fn parse_value(&self) -> Option<NumberValue> {
+        let digits: String = self.value.chars().filter(|ch| *ch != '_').collect();
+        let radix = match self.kind {
+            NumberKind::Hexadecimal => 16,
+            NumberKind::Binary => 2,
+            NumberKind::Octal => 8,
+            NumberKind::Integer | NumberKind::Decimal => 10,
+        };
+
+        let is_float = self.kind == NumberKind::Decimal
+            || self.postfix.as_ref().is_some_and(NumberSuffix::is_float);
+        if is_float {
+            let value: f64 = digits.parse().ok()?;
+            return value.is_finite().then_some(NumberValue::Float(value));
+        }
+
+        let digits = if radix == 10 {
+            &digits[..]
+        } else {
+            &digits[2..]
+        };
+        u128::from_str_radix(digits, radix)
+            .ok()
+            .map(NumberValue::Integer)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum NumberValue {
+    Integer(u128),
+    Float(f64),
+}
+
+/// The type a number literal is given with a suffix: `255u8`, `1.5f32`
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum NumberSuffix {
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Isize,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Usize,
+    F32,
+    F64,
+}
+
+impl NumberSuffix {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let suffix = match name {
+            "i8" => NumberSuffix::I8,
+            "i16" => NumberSuffix::I16,
+            "i32" => NumberSuffix::I32,
+            "i64" => NumberSuffix::I64,
+            "i128" => NumberSuffix::I128,
+            "isize" => NumberSuffix::Isize,
+            "u8" => NumberSuffix::U8,
+            "u16" => NumberSuffix::U16,
+            "u32" => NumberSuffix::U32,
+            "u64" => NumberSuffix::U64,
+            "u128" => NumberSuffix::U128,
+            "usize" => NumberSuffix::Usize,
+            "f32" => NumberSuffix::F32,
+            "f64" => NumberSuffix::F64,
+            _ => return None,
+        };
+        Some(suffix)
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, NumberSuffix::F32 | NumberSuffix::F64)
+    }
+
+    /// Whether `value`, negated if `is_negative`, is in range for the type.
+    /// `isize` and `usize` are taken to be 64 bits wide.
+    pub fn fits(&self, value: NumberValue, is_negative: bool) -> bool {
+        let bits = match self {
+            NumberSuffix::I8 | NumberSuffix::U8 => 8,
+            NumberSuffix::I16 | NumberSuffix::U16 => 16,
+            NumberSuffix::I32 | NumberSuffix::U32 => 32,
+            NumberSuffix::I64 | NumberSuffix::U64 | NumberSuffix::Isize | NumberSuffix::Usize => 64,
+            NumberSuffix::I128 | NumberSuffix::U128 => 128,
+            NumberSuffix::F32 => {
+                return matches!(value, NumberValue::Float(float) if float <= f32::MAX as f64)
+            }
+            NumberSuffix::F64 => return matches!(value, NumberValue::Float(_)),
+        };
+
+        let NumberValue::Integer(value) = value else {
+            return false;
+        };
+        let is_signed = matches!(
+            self,
+            NumberSuffix::I8
+                | NumberSuffix::I16
+                | NumberSuffix::I32
+                | NumberSuffix::I64
+                | NumberSuffix::I128
+                | NumberSuffix::Isize
+        );
+        // The magnitude of the most negative value is one more than the
+        // largest positive one; an unsigned type only has `-0`.
+        let max = match (is_signed, is_negative) {
+            (true, true) => 1u128 << (bits - 1),
+            (true, false) => (1u128 << (bits - 1)) - 1,
+            (false, true) => 0,
+            (false, false) => u128::MAX >> (128 - bits),
+        };
+        value <= max
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -115,6 +286,8 @@ pub struct TokenFrame {
     pub token: Token,
     pub start: usize,
     pub end: usize,
+    pub line: usize,
+    pub column: usize,
 }
 
 impl TokenFrame {
@@ -122,7 +295,18 @@ impl TokenFrame {
         Self {
             start: 0,
             end: 0,
+            line: 1,
+            column: 1,
             token: Token::EOF,
         }
     }
 }
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct WarningFrame {
+    pub warning: TokenWarning,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
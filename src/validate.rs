@@ -0,0 +1,61 @@
+use crate::ast::*;
+
+/// Checks that depend on where a node sits in the tree rather than on the
+/// tokens around it. Runs after parsing; the errors are added to the
+/// program's own.
+pub fn validate(program: &Program) -> Vec<ParseError> {
+    let mut errors: Vec<ParseError> = Vec::new();
+    for statement in &program.statements {
+        check_await(statement, false, &mut errors);
+        check_numbers(statement, &mut errors);
+    }
+    errors
+}
+
+/// `await` is only allowed in the body of an async function, closure or
+/// block. A plain function or closure nested inside one is not async itself.
+fn check_await(node: &Node, is_async: bool, errors: &mut Vec<ParseError>) {
+    let is_async = match node {
+        Node::Function(function) => function.is_async,
+        Node::Closure(closure) => closure.is_async,
+        Node::AsyncBlock(_) => true,
+        Node::Await(await_node) => {
+            if !is_async {
+                errors.push(ParseError::AwaitOutsideAsync(await_node.span));
+            }
+            is_async
+        }
+        _ => is_async,
+    };
+
+    for child in node.children() {
+        check_await(child, is_async, errors);
+    }
+}
+
+/// Number literals must fit their type. A negated literal is checked as a
+/// whole so that `-128i8` is in range while `128i8` is not.
+fn check_numbers(node: &Node, errors: &mut Vec<ParseError>) {
+    let (number, is_negative) = match node {
+        Node::Number(number) => (number, false),
+        Node::Unary(unary) if unary.operator == UnaryOperator::Negate => match &*unary.operand {
+            Node::Number(number) => (number, true),
+            operand => return check_numbers(operand, errors),
+        },
+        _ => {
+            for child in node.children() {
+                check_numbers(child, errors);
+            }
+            return;
+        }
+    };
+
+    let fits = match (number.parsed, &number.postfix) {
+        (Some(value), Some(suffix)) => suffix.fits(value, is_negative),
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+    if !fits {
+        errors.push(ParseError::NumberOverflow(number.span));
+    }
+}
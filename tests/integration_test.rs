@@ -1,23 +1,16 @@
 extern crate rust_compiler;
 
+use rust_compiler::ast::{ParseError, Program};
+use rust_compiler::lexer::Lexer;
+use rust_compiler::module::{load_modules, ModuleError};
 use rust_compiler::parser::Parser;
+use rust_compiler::syntax::{parse_syntax_tree, SyntaxElement, TriviaKind, PROGRAM_KIND};
+use rust_compiler::token::{Token, TokenError};
+use std::path::PathBuf;
 use std::{env, fs};
 
 fn test(test_name: &str) {
-    let current_dir = env::current_dir().expect("Failed to get current directory");
-    let test_dir = current_dir.join("tests").join(test_name);
-
-    let sample_foo_path = test_dir.join("sample.foo");
-    let sample_foo_path_str = sample_foo_path.to_str().unwrap();
-    let program = Parser::parse_file(sample_foo_path_str)
-        .expect(&format!("{} -> could not parse sample.foo", test_name));
-
-    let actual_json = serde_json::to_string_pretty(&program)
-        .expect(&format!("{} -> could not serialize program", test_name));
-
-    let expect_json_path = test_dir.join("expect.json");
-    let expect_json = fs::read_to_string(expect_json_path)
-        .expect(&format!("{} -> could not read expect.json", test_name));
+    let program = parse_sample(test_name);
 
     let parse_errors = program
         .errors
@@ -34,6 +27,43 @@ fn test(test_name: &str) {
         parse_errors
     );
 
+    assert_expected(test_name, &program);
+}
+
+/// Like `test`, but for samples that are expected to produce errors. The
+/// errors are part of the expected output.
+fn test_with_errors(test_name: &str) {
+    let program = parse_sample(test_name);
+
+    assert!(
+        !program.errors.is_empty(),
+        "{} expected parse errors but there were none",
+        test_name
+    );
+
+    assert_expected(test_name, &program);
+}
+
+fn test_dir(test_name: &str) -> PathBuf {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+    current_dir.join("tests").join(test_name)
+}
+
+fn parse_sample(test_name: &str) -> Program {
+    let sample_foo_path = test_dir(test_name).join("sample.foo");
+    let sample_foo_path_str = sample_foo_path.to_str().unwrap();
+    Parser::parse_file(sample_foo_path_str)
+        .unwrap_or_else(|_| panic!("{} -> could not parse sample.foo", test_name))
+}
+
+fn assert_expected(test_name: &str, program: &Program) {
+    let actual_json = serde_json::to_string_pretty(program)
+        .unwrap_or_else(|_| panic!("{} -> could not serialize program", test_name));
+
+    let expect_json_path = test_dir(test_name).join("expect.json");
+    let expect_json = fs::read_to_string(expect_json_path)
+        .unwrap_or_else(|_| panic!("{} -> could not read expect.json", test_name));
+
     assert_eq!(
         actual_json, expect_json,
         "{} program output does not match expected result.\n{}",
@@ -45,3 +75,348 @@ fn test(test_name: &str) {
 fn test_variable_statement() {
     test("variable_statement");
 }
+
+#[test]
+fn test_multiline_spans() {
+    test("multiline_spans");
+}
+
+#[test]
+fn test_block_values() {
+    test("block_values");
+}
+
+#[test]
+fn test_comments() {
+    test("comments");
+}
+
+#[test]
+fn test_template_literal() {
+    test("template_literal");
+}
+
+#[test]
+fn test_binary_expressions() {
+    test("binary_expressions");
+}
+
+#[test]
+fn test_unary_expressions() {
+    test("unary_expressions");
+}
+
+#[test]
+fn test_postfix_expressions() {
+    test("postfix_expressions");
+}
+
+#[test]
+fn test_conditional_expressions() {
+    test("conditional_expressions");
+}
+
+#[test]
+fn test_literals() {
+    test("literals");
+}
+
+#[test]
+fn test_doc_comments() {
+    test("doc_comments");
+}
+
+#[test]
+fn test_error_recovery() {
+    test_with_errors("error_recovery");
+}
+
+#[test]
+fn test_max_errors() {
+    let mut lexer = Lexer::new();
+    lexer.read_source("let = 1; let = 2; let = 3; let = 4; let ok = 5;");
+    lexer.finish();
+    let mut tokens = Vec::new();
+    while let Some(frame) = lexer.next_token() {
+        tokens.push(frame);
+    }
+
+    let program = Parser::new(tokens).with_max_errors(2).parse_program();
+
+    assert_eq!(program.statements.len(), 0);
+    assert_eq!(program.errors.len(), 3);
+    for error in &program.errors[..2] {
+        match error {
+            ParseError::UnexpectedToken(frame) => assert_eq!(frame.token, Token::Equal),
+            other => panic!("Expected an unexpected '=' but got {:?}", other),
+        }
+    }
+    assert_eq!(program.errors[2], ParseError::TooManyErrors);
+}
+
+#[test]
+fn test_nul_in_source() {
+    let program = Parser::parse_source(vec!["let x = 1;\0 let y = 2;"]);
+    assert_eq!(program.statements.len(), 2);
+    match &program.errors[..] {
+        [ParseError::UnexpectedToken(frame)] => {
+            assert_eq!(frame.token, Token::Error(TokenError::Illegal('\0')))
+        }
+        other => panic!("Expected one illegal NUL but got {:?}", other),
+    }
+}
+
+#[test]
+fn test_function_declaration() {
+    test("function_declaration");
+}
+
+#[test]
+fn test_if_statement() {
+    test("if_statement");
+}
+
+#[test]
+fn test_return_statement() {
+    test("return_statement");
+}
+
+#[test]
+fn test_loops() {
+    test("loops");
+}
+
+#[test]
+fn test_loop_errors() {
+    test_with_errors("loop_errors");
+}
+
+#[test]
+fn test_match_expression() {
+    test("match_expression");
+}
+
+#[test]
+fn test_type_annotations() {
+    test("type_annotations");
+}
+
+#[test]
+fn test_type_declaration() {
+    test("type_declaration");
+}
+
+#[test]
+fn test_trait_impl() {
+    test("trait_impl");
+}
+
+#[test]
+fn test_async_await() {
+    test("async_await");
+}
+
+#[test]
+fn test_await_errors() {
+    test_with_errors("await_errors");
+}
+
+#[test]
+fn test_closures() {
+    test("closures");
+}
+
+#[test]
+fn test_compound_literals() {
+    test("compound_literals");
+}
+
+#[test]
+fn test_assignment() {
+    test("assignment");
+}
+
+#[test]
+fn test_assignment_errors() {
+    test_with_errors("assignment_errors");
+}
+
+#[test]
+fn test_operators() {
+    test("operators");
+}
+
+#[test]
+fn test_number_literals() {
+    test("number_literals");
+}
+
+#[test]
+fn test_number_overflow() {
+    test_with_errors("number_overflow");
+}
+
+#[test]
+fn test_string_literals() {
+    test("string_literals");
+}
+
+#[test]
+fn test_unicode_identifiers() {
+    test("unicode_identifiers");
+}
+
+#[test]
+fn test_use_declaration() {
+    test("use_declaration");
+}
+
+#[test]
+fn test_module_loader() {
+    let dir = test_dir("module_loader");
+    let tree = load_modules(dir.join("main.foo")).expect("could not load main.foo");
+
+    let root = &tree.root;
+    assert_eq!(root.name, "main");
+    let names = |path: &[&str]| {
+        let module = root.find(path).unwrap();
+        module
+            .submodules
+            .iter()
+            .map(|module| module.name.as_str())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(&[]), vec!["math", "net", "shapes"]);
+    assert_eq!(names(&["math"]), vec!["consts"]);
+    assert_eq!(names(&["net"]), vec!["http"]);
+
+    let consts = root.find(&["math", "consts"]).unwrap();
+    assert_eq!(consts.file, Some(dir.join("math").join("consts.foo")));
+    assert_eq!(consts.program.statements.len(), 1);
+
+    // `net` is only a directory.
+    let net = root.find(&["net"]).unwrap();
+    assert_eq!(net.file, None);
+    assert!(net.program.statements.is_empty());
+
+    for module in [&["math"][..], &["shapes"], &["net", "http"]] {
+        let module = root.find(module).unwrap();
+        assert!(
+            module.program.errors.is_empty(),
+            "{:?}",
+            module.program.errors
+        );
+    }
+
+    assert_eq!(
+        tree.errors,
+        vec![ModuleError::UnresolvedImport {
+            file: dir.join("main.foo"),
+            path: vec!["missing".to_string(), "thing".to_string()],
+        }]
+    );
+}
+
+#[test]
+fn test_syntax_tree_round_trip() {
+    let mut sources: Vec<String> = Vec::new();
+    for entry in fs::read_dir(test_dir("")).unwrap() {
+        let sample = entry.unwrap().path().join("sample.foo");
+        if let Ok(source) = fs::read_to_string(sample) {
+            sources.push(source);
+        }
+    }
+    assert!(sources.len() > 20);
+
+    sources.extend(
+        [
+            "",
+            "   \n\t\n",
+            "let x = 1;",
+            "let a\u{00A0}=\u{3000}1;\r\n// trailing\r\n",
+            "let = 1; let ok = \"caf\u{00E9}\";   ",
+            "func f() {\n    /* unterminated",
+            "let s = \"unterminated\n",
+            "/// docs\nlet x = 1; ☺ ?? ",
+            "let x = 1;\0 let y = 2;",
+        ]
+        .map(String::from),
+    );
+
+    for source in sources {
+        let tree = parse_syntax_tree(&source);
+        assert_eq!(tree.root.text(), source);
+        assert_eq!(tree.root.range(), 0..source.len());
+        for token in tree.root.tokens() {
+            assert_eq!(&source[token.text_range()], token.text());
+        }
+    }
+}
+
+#[test]
+fn test_syntax_tree_trivia() {
+    let source = "let x = 1; // one\n\n/// Adds.\nfunc add() {\n    return 2;\n}\n";
+    let tree = parse_syntax_tree(source);
+    let root = &tree.root;
+    assert!(tree.program.errors.is_empty());
+    assert_eq!(root.kind(), PROGRAM_KIND);
+
+    let kinds: Vec<&str> = root.child_nodes().iter().map(|node| node.kind()).collect();
+    assert_eq!(kinds, vec!["Variable", "Function"]);
+
+    let tokens = root.tokens();
+    let semi = &tokens[4];
+    assert_eq!(semi.token(), &Token::Semi);
+    let trailing: Vec<(TriviaKind, &str)> = semi
+        .trailing()
+        .iter()
+        .map(|trivia| (trivia.kind, trivia.text.as_str()))
+        .collect();
+    assert_eq!(
+        trailing,
+        vec![
+            (TriviaKind::Whitespace, " "),
+            (TriviaKind::Comment, "// one")
+        ]
+    );
+
+    let func = &tokens[5];
+    assert_eq!(func.text(), "func");
+    let leading: Vec<(TriviaKind, &str)> = func
+        .leading()
+        .iter()
+        .map(|trivia| (trivia.kind, trivia.text.as_str()))
+        .collect();
+    assert_eq!(
+        leading,
+        vec![
+            (TriviaKind::Newline, "\n"),
+            (TriviaKind::Newline, "\n"),
+            (TriviaKind::DocComment, "/// Adds."),
+            (TriviaKind::Newline, "\n"),
+        ]
+    );
+    assert_eq!(func.text_range(), 29..33);
+
+    // The file ends with the line break after `}`, held by `EOF`.
+    let eof = tokens.last().unwrap();
+    assert_eq!(eof.token(), &Token::EOF);
+    assert_eq!(eof.text(), "");
+    assert_eq!(eof.leading()[0].text, "\n");
+    assert_eq!(eof.parent().kind(), PROGRAM_KIND);
+
+    let function = &root.child_nodes()[1];
+    assert_eq!(function.range(), 17..source.len() - 1);
+    let block = function
+        .child_nodes()
+        .into_iter()
+        .find(|node| node.kind() == "Block")
+        .unwrap();
+    assert_eq!(block.parent().unwrap().kind(), "Function");
+    let statement = match &block.children()[1] {
+        SyntaxElement::Node(node) => node.clone(),
+        SyntaxElement::Token(token) => panic!("Expected a statement but got {:?}", token),
+    };
+    assert_eq!(statement.kind(), "Return");
+    assert_eq!(statement.text(), "\n    return 2;");
+}